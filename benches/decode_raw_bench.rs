@@ -0,0 +1,76 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use rand::{Rng, SeedableRng};
+use ruzstd::{BlockDecodingStrategy, FrameDecoder};
+
+/// Builds a frame that stores the data in raw blocks, like an encoder does for incompressible input
+fn raw_frame(data: &[u8], window_descriptor: u8) -> Vec<u8> {
+    const MAX_BLOCK_SIZE: usize = 128 * 1024;
+
+    // no content size, no checksum
+    let mut frame = vec![0x28, 0xb5, 0x2f, 0xfd, 0x00, window_descriptor];
+    let mut blocks = data.chunks(MAX_BLOCK_SIZE).peekable();
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none() as u32;
+        let header = ((block.len() as u32) << 3) | last;
+        frame.extend_from_slice(&header.to_le_bytes()[..3]);
+        frame.extend_from_slice(block);
+    }
+    frame
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    const DATA_SIZE: usize = 8 * 1024 * 1024;
+
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(0xDEADBEEF);
+    let mut data = vec![0u8; DATA_SIZE];
+    rng.fill(data.as_mut_slice());
+    // 8MB window
+    let frame = raw_frame(&data, 0x68);
+
+    let mut frame_dec = FrameDecoder::new();
+    let mut group = c.benchmark_group("decode raw blocks");
+    group.throughput(Throughput::Bytes(DATA_SIZE as u64));
+    group.bench_function("incompressible 8MB", |b| {
+        b.iter(|| {
+            let mut source = frame.as_slice();
+            frame_dec.reset(&mut source).unwrap();
+            frame_dec
+                .decode_blocks(&mut source, BlockDecodingStrategy::All)
+                .unwrap();
+            black_box(frame_dec.can_collect())
+        })
+    });
+    group.finish();
+
+    // with a window of 128KB each raw block covers the whole window, so decode_slice_to_vec copies the
+    // raw blocks straight from the input to the output
+    let path = std::env::temp_dir().join("ruzstd_decode_raw_bench.zst");
+    std::fs::write(&path, raw_frame(&data, 0x38)).unwrap();
+    let frame = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut group = c.benchmark_group("decode raw frame from a file");
+    group.throughput(Throughput::Bytes(DATA_SIZE as u64));
+    group.bench_function("through the decodebuffer", |b| {
+        b.iter(|| {
+            let mut output = Vec::with_capacity(DATA_SIZE);
+            frame_dec
+                .decode_with(frame.as_slice(), |chunk| output.extend_from_slice(chunk))
+                .unwrap();
+            black_box(output)
+        })
+    });
+    group.bench_function("decode_slice_to_vec", |b| {
+        b.iter(|| {
+            let mut output = Vec::with_capacity(DATA_SIZE);
+            frame_dec
+                .decode_slice_to_vec(frame.as_slice(), &mut output)
+                .unwrap();
+            black_box(output)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -21,7 +21,7 @@ impl<'s> BitReaderReversed<'s> {
         self.idx + self.bits_in_container as isize
     }
 
-    pub fn new(source: &'s [u8]) -> BitReaderReversed<'_> {
+    pub fn new(source: &'s [u8]) -> BitReaderReversed<'s> {
         BitReaderReversed {
             idx: source.len() as isize * 8,
             source,
@@ -35,7 +35,7 @@ impl<'s> BitReaderReversed<'s> {
     fn refill_container(&mut self) {
         let byte_idx = self.byte_idx() as usize;
 
-        let retain_bytes = (self.bits_in_container + 7) / 8;
+        let retain_bytes = self.bits_in_container.div_ceil(8);
         let want_to_read_bits = 64 - (retain_bytes * 8);
 
         // if there are >= 8 byte left to read we go a fast path:
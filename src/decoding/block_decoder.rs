@@ -3,9 +3,9 @@ use super::super::blocks::block::BlockType;
 use super::super::blocks::literals_section::LiteralsSection;
 use super::super::blocks::literals_section::LiteralsSectionType;
 use super::super::blocks::sequence_section::SequencesHeader;
-use super::literals_section_decoder::{decode_literals, DecompressLiteralsError};
+use super::literals_section_decoder::{decode_literals_with_position, DecompressLiteralsError};
 use super::sequence_execution::ExecuteSequencesError;
-use super::sequence_section_decoder::decode_sequences;
+use super::sequence_section_decoder::decode_sequences_with_position;
 use super::sequence_section_decoder::DecodeSequenceError;
 use crate::blocks::literals_section::LiteralsSectionParseError;
 use crate::blocks::sequence_section::SequencesHeaderParseError;
@@ -221,8 +221,17 @@ impl From<ExecuteSequencesError> for DecompressBlockError {
 pub enum DecodeBlockContentError {
     DecoderStateIsFailed,
     ExpectedHeaderOfPreviousBlock,
-    ReadError { step: BlockType, source: io::Error },
-    DecompressBlockError(DecompressBlockError),
+    ReadError {
+        step: BlockType,
+        source: io::Error,
+    },
+    /// Decompressing a compressed block failed. `at_offset` is the offset of the section that failed to decode,
+    /// relative to the start of the block content, or to the start of the frame if returned by the
+    /// [FrameDecoder](crate::frame_decoder::FrameDecoder)
+    DecompressBlockError {
+        at_offset: u64,
+        source: DecompressBlockError,
+    },
 }
 
 #[cfg(feature = "std")]
@@ -230,7 +239,7 @@ impl std::error::Error for DecodeBlockContentError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             DecodeBlockContentError::ReadError { step: _, source } => Some(source),
-            DecodeBlockContentError::DecompressBlockError(source) => Some(source),
+            DecodeBlockContentError::DecompressBlockError { source, .. } => Some(source),
             _ => None,
         }
     }
@@ -253,14 +262,25 @@ impl core::fmt::Display for DecodeBlockContentError {
             DecodeBlockContentError::ReadError { step, source } => {
                 write!(f, "Error while reading bytes for {}: {}", step, source,)
             }
-            DecodeBlockContentError::DecompressBlockError(e) => write!(f, "{:?}", e),
+            DecodeBlockContentError::DecompressBlockError { at_offset, source } => {
+                write!(f, "At offset {}: {:?}", at_offset, source)
+            }
         }
     }
 }
 
-impl From<DecompressBlockError> for DecodeBlockContentError {
-    fn from(val: DecompressBlockError) -> Self {
-        Self::DecompressBlockError(val)
+impl DecodeBlockContentError {
+    /// Moves the offset this error was found at by `offset` bytes, so it can be made relative to the start of the frame
+    pub(crate) fn offset_by(self, offset: u64) -> Self {
+        match self {
+            DecodeBlockContentError::DecompressBlockError { at_offset, source } => {
+                DecodeBlockContentError::DecompressBlockError {
+                    at_offset: at_offset + offset,
+                    source,
+                }
+            }
+            other => other,
+        }
     }
 }
 
@@ -318,29 +338,14 @@ impl BlockDecoder {
                 Ok(1)
             }
             BlockType::Raw => {
-                const BATCH_SIZE: usize = 128 * 1024;
-                let mut buf = [0u8; BATCH_SIZE];
-                let full_reads = header.decompressed_size / BATCH_SIZE as u32;
-                let single_read_size = header.decompressed_size % BATCH_SIZE as u32;
-
-                for _ in 0..full_reads {
-                    source.read_exact(&mut buf[..]).map_err(|err| {
-                        DecodeBlockContentError::ReadError {
-                            step: block_type,
-                            source: err,
-                        }
-                    })?;
-                    workspace.buffer.push(&buf[..]);
-                }
-
-                let smaller = &mut buf[..single_read_size as usize];
-                source
-                    .read_exact(smaller)
+                // Raw blocks need none of the entropy decoding, the content is read directly into the decodebuffer
+                workspace
+                    .buffer
+                    .push_from_reader(&mut source, header.decompressed_size as usize)
                     .map_err(|err| DecodeBlockContentError::ReadError {
                         step: block_type,
                         source: err,
                     })?;
-                workspace.buffer.push(smaller);
 
                 self.internal_state = DecoderState::ReadyToDecodeNextHeader;
                 Ok(u64::from(header.decompressed_size))
@@ -351,7 +356,12 @@ impl BlockDecoder {
             }
 
             BlockType::Compressed => {
-                self.decompress_block(header, workspace, source)?;
+                let mut section_offset = 0;
+                self.decompress_block(header, workspace, source, &mut section_offset)
+                    .map_err(|err| DecodeBlockContentError::DecompressBlockError {
+                        at_offset: u64::from(section_offset),
+                        source: err,
+                    })?;
 
                 self.internal_state = DecoderState::ReadyToDecodeNextHeader;
                 Ok(u64::from(header.content_size))
@@ -364,6 +374,7 @@ impl BlockDecoder {
         header: &BlockHeader,
         workspace: &mut DecoderScratch, //reuse this as often as possible. Not only if the trees are reused but also reuse the allocations when building new trees
         mut source: impl Read,
+        section_offset: &mut u32,
     ) -> Result<(), DecompressBlockError> {
         workspace
             .block_content_buffer
@@ -375,6 +386,7 @@ impl BlockDecoder {
         let mut section = LiteralsSection::new();
         let bytes_in_literals_header = section.parse_from_header(raw)?;
         let raw = &raw[bytes_in_literals_header as usize..];
+        *section_offset = u32::from(bytes_in_literals_header);
         vprintln!(
             "Found {} literalssection with regenerated size: {}, and compressed size: {:?}",
             section.ls_type,
@@ -402,12 +414,18 @@ impl BlockDecoder {
         vprintln!("Slice for literals: {}", raw_literals.len());
 
         workspace.literals_buffer.clear(); //all literals of the previous block must have been used in the sequence execution anyways. just be defensive here
-        let bytes_used_in_literals_section = decode_literals(
+        let mut position = 0;
+        let bytes_used_in_literals_section = decode_literals_with_position(
             &section,
             &mut workspace.huf,
             raw_literals,
             &mut workspace.literals_buffer,
-        )?;
+            &mut position,
+        )
+        .map_err(|e| {
+            *section_offset += position;
+            e
+        })?;
         assert!(
             section.regenerated_size == workspace.literals_buffer.len() as u32,
             "Wrong number of literals: {}, Should have been: {}",
@@ -417,11 +435,13 @@ impl BlockDecoder {
         assert!(bytes_used_in_literals_section == upper_limit_for_literals as u32);
 
         let raw = &raw[upper_limit_for_literals..];
+        *section_offset += upper_limit_for_literals as u32;
         vprintln!("Slice for sequences with headers: {}", raw.len());
 
         let mut seq_section = SequencesHeader::new();
         let bytes_in_sequence_header = seq_section.parse_from_header(raw)?;
         let raw = &raw[bytes_in_sequence_header as usize..];
+        *section_offset += u32::from(bytes_in_sequence_header);
         vprintln!(
             "Found sequencessection with sequences: {} and size: {}",
             seq_section.num_sequences,
@@ -438,12 +458,18 @@ impl BlockDecoder {
         vprintln!("Slice for sequences: {}", raw.len());
 
         if seq_section.num_sequences != 0 {
-            decode_sequences(
+            let mut position = 0;
+            decode_sequences_with_position(
                 &seq_section,
                 raw,
                 &mut workspace.fse,
                 &mut workspace.sequences,
-            )?;
+                &mut position,
+            )
+            .map_err(|e| {
+                *section_offset += position as u32;
+                e
+            })?;
             vprintln!("Executing sequences");
             execute_sequences(workspace)?;
         } else {
@@ -11,8 +11,13 @@ pub struct DecodeBuffer {
 
     pub window_size: usize,
     total_output_counter: u64,
+    /// The buffer is never shrunk below this capacity, see [DecodeBuffer::preallocate]
+    min_capacity: usize,
     #[cfg(feature = "hash")]
     pub hash: twox_hash::XxHash64,
+    /// If false, drained bytes are not fed into `hash`
+    #[cfg(feature = "hash")]
+    pub hash_enabled: bool,
 }
 
 #[derive(Debug)]
@@ -42,6 +47,11 @@ impl core::fmt::Display for DecodeBufferError {
     }
 }
 
+/// A block never decodes to more than this, so the buffer needs at most this much on top of the window
+pub(crate) const MAX_BLOCK_SIZE: usize = 128 * 1024;
+/// The buffer is shrunk on reset if it is more than this many times bigger than the next frame needs
+const SHRINK_FACTOR: usize = 4;
+
 impl Read for DecodeBuffer {
     fn read(&mut self, target: &mut [u8]) -> Result<usize, Error> {
         let max_amount = self.can_drain_to_window_size().unwrap_or(0);
@@ -64,15 +74,35 @@ impl DecodeBuffer {
             dict_content: Vec::new(),
             window_size,
             total_output_counter: 0,
+            min_capacity: 0,
             #[cfg(feature = "hash")]
             hash: twox_hash::XxHash64::with_seed(0),
+            #[cfg(feature = "hash")]
+            hash_enabled: true,
         }
     }
 
     pub fn reset(&mut self, window_size: usize) {
+        self.reset_for_content(window_size, None);
+    }
+
+    /// Like [DecodeBuffer::reset] but for a frame that declares to decode to `content_size` bytes.
+    ///
+    /// A frame may declare a window that is much bigger than its content. The buffer never has to hold more
+    /// than the content, so only that much memory is reserved then.
+    pub fn reset_for_content(&mut self, window_size: usize, content_size: Option<u64>) {
         self.window_size = window_size;
         self.buffer.clear();
-        self.buffer.reserve(self.window_size);
+        let reserve = match content_size {
+            Some(content_size) if content_size < window_size as u64 => content_size as usize,
+            _ => window_size,
+        };
+        // Don't hold on to a huge buffer from a previous frame if this frame needs a lot less
+        let needed = usize::max(reserve + MAX_BLOCK_SIZE, self.min_capacity);
+        if self.buffer.capacity() > needed * SHRINK_FACTOR {
+            self.buffer.shrink_to(needed);
+        }
+        self.buffer.reserve(reserve);
         self.dict_content.clear();
         self.total_output_counter = 0;
         #[cfg(feature = "hash")]
@@ -81,10 +111,35 @@ impl DecodeBuffer {
         }
     }
 
+    /// How many bytes have been decoded into this buffer since the last reset, including the ones that were drained already
+    pub fn total_output(&self) -> u64 {
+        self.total_output_counter
+    }
+
     pub fn len(&self) -> usize {
         self.buffer.len()
     }
 
+    /// How many bytes the buffer can hold without allocating
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Allocate room for `capacity` bytes now. The buffer is never shrunk below that capacity afterwards,
+    /// neither on reset nor by [DecodeBuffer::shrink_to_fit].
+    pub fn preallocate(&mut self, capacity: usize) {
+        self.min_capacity = capacity;
+        self.buffer
+            .reserve(capacity.saturating_sub(self.buffer.len()));
+    }
+
+    /// Release as much memory as possible while keeping all bytes that are currently in the buffer
+    /// and the capacity requested with [DecodeBuffer::preallocate]
+    pub fn shrink_to_fit(&mut self) {
+        self.buffer.shrink_to(self.min_capacity);
+        self.dict_content.shrink_to_fit();
+    }
+
     pub fn is_empty(&self) -> bool {
         self.buffer.is_empty()
     }
@@ -94,6 +149,37 @@ impl DecodeBuffer {
         self.total_output_counter += data.len() as u64;
     }
 
+    /// Appends `len` bytes read from the source, e.g. the content of a raw block
+    pub fn push_from_reader(&mut self, source: impl Read, len: usize) -> Result<(), Error> {
+        self.buffer.extend_from_reader(source, len)?;
+        self.total_output_counter += len as u64;
+        Ok(())
+    }
+
+    /// Appends `data` like [DecodeBuffer::push], but only the last `keep` bytes of it are copied into the buffer.
+    /// Everything in the buffer and the rest of `data` is appended to `output` directly.
+    ///
+    /// Later matches can only reach back `window_size` bytes, so `keep` must be at least that much
+    /// (or all of `data`), unless nothing is decoded into the buffer anymore.
+    pub fn push_past_window(&mut self, data: &[u8], keep: usize, output: &mut Vec<u8>) {
+        let keep = usize::min(keep, data.len());
+        let (direct, kept) = data.split_at(data.len() - keep);
+
+        let len = self.buffer.len();
+        // can't fail, the closure never returns an error
+        let _ = self.drain_to(len, |buf| {
+            output.extend_from_slice(buf);
+            (buf.len(), Ok(()))
+        });
+        #[cfg(feature = "hash")]
+        if self.hash_enabled {
+            self.hash.write(direct);
+        }
+        output.extend_from_slice(direct);
+        self.total_output_counter += direct.len() as u64;
+        self.push(kept);
+    }
+
     pub fn repeat(&mut self, offset: usize, match_length: usize) -> Result<(), DecodeBufferError> {
         if offset > self.buffer.len() {
             self.repeat_from_dict(offset, match_length)
@@ -186,6 +272,8 @@ impl DecodeBuffer {
                 let high = low + match_length;
                 let dict_slice = &self.dict_content[low..high];
                 self.buffer.extend(dict_slice);
+
+                self.total_output_counter += match_length as u64;
             }
             Ok(())
         } else {
@@ -242,7 +330,7 @@ impl DecodeBuffer {
     pub fn drain(&mut self) -> Vec<u8> {
         let (slice1, slice2) = self.buffer.as_slices();
         #[cfg(feature = "hash")]
-        {
+        if self.hash_enabled {
             self.hash.write(slice1);
             self.hash.write(slice2);
         }
@@ -261,6 +349,20 @@ impl DecodeBuffer {
         Ok(len)
     }
 
+    /// The first contiguous part of the buffer, up to `max` bytes long. Use [DecodeBuffer::consume] to remove
+    /// the bytes from the buffer once they have been used.
+    pub fn peek(&self, max: usize) -> &[u8] {
+        let (slice1, _) = self.buffer.as_slices();
+        &slice1[..usize::min(max, slice1.len())]
+    }
+
+    /// Removes `amount` bytes from the start of the buffer like any of the drain functions would
+    pub fn consume(&mut self, amount: usize) {
+        let amount = usize::min(amount, self.buffer.len());
+        // can't fail, the closure never returns an error
+        let _ = self.drain_to(amount, |buf| (buf.len(), Ok(())));
+    }
+
     pub fn read_all(&mut self, target: &mut [u8]) -> Result<usize, Error> {
         let amount = self.buffer.len().min(target.len());
 
@@ -310,7 +412,9 @@ impl DecodeBuffer {
         if n1 != 0 {
             let (written1, res1) = write_bytes(&slice1[..n1]);
             #[cfg(feature = "hash")]
-            self.hash.write(&slice1[..written1]);
+            if self.hash_enabled {
+                self.hash.write(&slice1[..written1]);
+            }
             drain_guard.amount += written1;
 
             // Apparently this is what clippy thinks is the best way of expressing this
@@ -321,7 +425,9 @@ impl DecodeBuffer {
             if written1 == n1 && n2 != 0 {
                 let (written2, res2) = write_bytes(&slice2[..n2]);
                 #[cfg(feature = "hash")]
-                self.hash.write(&slice2[..written2]);
+                if self.hash_enabled {
+                    self.hash.write(&slice2[..written2]);
+                }
                 drain_guard.amount += written2;
 
                 // Apparently this is what clippy thinks is the best way of expressing this
@@ -404,6 +510,49 @@ mod tests {
         assert_eq!(short_writer.buf.len(), repeats * 50 + 100);
     }
 
+    #[test]
+    fn shrink_after_large_frame() {
+        let mut decode_buf = DecodeBuffer::new(1024);
+        decode_buf.reset(1024);
+        for _ in 0..100 {
+            decode_buf.push(&[0xAB; 10 * 1024]);
+        }
+        assert!(decode_buf.capacity() >= 1000 * 1024);
+
+        // a frame that does not need the buffer the previous one left behind
+        decode_buf.reset(1024);
+        assert!(decode_buf.capacity() >= 1024);
+        assert!(decode_buf.capacity() < 1000 * 1024);
+
+        // a frame with a big enough window keeps the buffer as it is
+        let cap = decode_buf.capacity();
+        decode_buf.reset(64 * 1024);
+        assert_eq!(decode_buf.capacity(), cap);
+
+        decode_buf.push(b"0123456789");
+        decode_buf.shrink_to_fit();
+        assert_eq!(decode_buf.capacity(), 10);
+        assert_eq!(decode_buf.drain(), b"0123456789");
+        decode_buf.shrink_to_fit();
+        assert_eq!(decode_buf.capacity(), 0);
+    }
+
+    #[test]
+    fn reserve_for_small_content() {
+        let mut decode_buf = DecodeBuffer::new(0);
+        decode_buf.reset_for_content(64 * 1024 * 1024, Some(5));
+        assert_eq!(decode_buf.window_size, 64 * 1024 * 1024);
+        assert!(decode_buf.capacity() >= 5);
+        assert!(decode_buf.capacity() < 1024 * 1024);
+
+        decode_buf.push(b"hello");
+        assert_eq!(decode_buf.drain(), b"hello");
+
+        // without a declared content size the whole window is reserved
+        decode_buf.reset_for_content(1024 * 1024, None);
+        assert!(decode_buf.capacity() >= 1024 * 1024);
+    }
+
     #[test]
     fn wouldblock_writer() {
         struct WouldblockWriter {
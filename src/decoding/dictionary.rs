@@ -149,4 +149,19 @@ impl Dictionary {
 
         Ok(new_dict)
     }
+
+    /// Derives a dictionary id from the dictionary content, for dictionaries that were created without one.
+    ///
+    /// Like the reference implementation this is the XXH64 of the content mapped into the range `[32768, 2^31)`,
+    /// so it is never zero and stays out of the ranges reserved for registered dictionaries.
+    /// The same content always results in the same id.
+    #[cfg(feature = "hash")]
+    pub fn derive_id(content: &[u8]) -> u32 {
+        use core::hash::Hasher;
+
+        let mut hasher = twox_hash::XxHash64::with_seed(0);
+        hasher.write(content);
+        let hash = hasher.finish();
+        (hash % ((1 << 31) - 32768)) as u32 + 32768
+    }
 }
@@ -4,7 +4,7 @@
 use super::super::blocks::literals_section::{LiteralsSection, LiteralsSectionType};
 use super::bit_reader_reverse::{BitReaderReversed, GetBitsError};
 use super::scratch::HuffmanScratch;
-use crate::huff0::{HuffmanDecoder, HuffmanDecoderError, HuffmanTableError};
+use crate::huff0::{HuffmanDecoder, HuffmanDecoderError, HuffmanTable, HuffmanTableError};
 use alloc::vec::Vec;
 
 #[derive(Debug)]
@@ -16,11 +16,29 @@ pub enum DecompressLiteralsError {
     HuffmanTableError(HuffmanTableError),
     HuffmanDecoderError(HuffmanDecoderError),
     UninitializedHuffmanTable,
-    MissingBytesForJumpHeader { got: usize },
-    MissingBytesForLiterals { got: usize, needed: usize },
-    ExtraPadding { skipped_bits: i32 },
-    BitstreamReadMismatch { read_til: isize, expected: isize },
-    DecodedLiteralCountMismatch { decoded: usize, expected: usize },
+    MissingBytesForJumpHeader {
+        got: usize,
+    },
+    MissingBytesForLiterals {
+        got: usize,
+        needed: usize,
+    },
+    ExtraPadding {
+        skipped_bits: i32,
+    },
+    BitstreamReadMismatch {
+        read_til: isize,
+        expected: isize,
+    },
+    DecodedLiteralCountMismatch {
+        decoded: usize,
+        expected: usize,
+    },
+    StreamLiteralCountMismatch {
+        stream: u8,
+        decoded: usize,
+        expected: usize,
+    },
 }
 
 #[cfg(feature = "std")]
@@ -86,6 +104,17 @@ impl core::fmt::Display for DecompressLiteralsError {
                     decoded, expected,
                 )
             }
+            DecompressLiteralsError::StreamLiteralCountMismatch {
+                stream,
+                decoded,
+                expected,
+            } => {
+                write!(
+                    f,
+                    "Huffman stream {} decoded {} literals, should have been: {}",
+                    stream, decoded, expected,
+                )
+            }
         }
     }
 }
@@ -114,6 +143,18 @@ pub fn decode_literals(
     scratch: &mut HuffmanScratch,
     source: &[u8],
     target: &mut Vec<u8>,
+) -> Result<u32, DecompressLiteralsError> {
+    decode_literals_with_position(section, scratch, source, target, &mut 0)
+}
+
+/// Like [decode_literals] but keeps `position` at the offset into `source` of the part that is currently decoded
+/// (the huffman table, the jump table or one of the streams), so errors can be located in the input.
+pub(crate) fn decode_literals_with_position(
+    section: &LiteralsSection,
+    scratch: &mut HuffmanScratch,
+    source: &[u8],
+    target: &mut Vec<u8>,
+    position: &mut u32,
 ) -> Result<u32, DecompressLiteralsError> {
     match section.ls_type {
         LiteralsSectionType::Raw => {
@@ -125,7 +166,7 @@ pub fn decode_literals(
             Ok(1)
         }
         LiteralsSectionType::Compressed | LiteralsSectionType::Treeless => {
-            let bytes_read = decompress_literals(section, scratch, source, target)?;
+            let bytes_read = decompress_literals(section, scratch, source, target, position)?;
 
             //return sum of used bytes
             Ok(bytes_read)
@@ -142,6 +183,7 @@ fn decompress_literals(
     scratch: &mut HuffmanScratch,
     source: &[u8],
     target: &mut Vec<u8>,
+    position: &mut u32,
 ) -> Result<u32, DecompressLiteralsError> {
     use DecompressLiteralsError as err;
 
@@ -158,15 +200,14 @@ fn decompress_literals(
             bytes_read += scratch.table.build_decoder(source)?;
             vprintln!("Built huffman table using {} bytes", bytes_read);
         }
-        LiteralsSectionType::Treeless => {
-            if scratch.table.max_num_bits == 0 {
-                return Err(err::UninitializedHuffmanTable);
-            }
+        LiteralsSectionType::Treeless if scratch.table.max_num_bits == 0 => {
+            return Err(err::UninitializedHuffmanTable);
         }
         _ => { /* nothing to do, huffman tree has been provided by previous block */ }
     }
 
     let source = &source[bytes_read as usize..];
+    *position = bytes_read;
 
     if num_streams == 4 {
         //build jumptable
@@ -192,59 +233,38 @@ fn decompress_literals(
         let stream3 = &source[jump2..jump3];
         let stream4 = &source[jump3..];
 
-        for stream in &[stream1, stream2, stream3, stream4] {
-            let mut decoder = HuffmanDecoder::new(&scratch.table);
-            let mut br = BitReaderReversed::new(stream);
-            //skip the 0 padding at the end of the last byte of the bit stream and throw away the first 1 found
-            let mut skipped_bits = 0;
-            loop {
-                let val = br.get_bits(1)?;
-                skipped_bits += 1;
-                if val == 1 || skipped_bits > 8 {
-                    break;
-                }
-            }
-            if skipped_bits > 8 {
-                //if more than 7 bits are 0, this is not the correct end of the bitstream. Either a bug or corrupted data
-                return Err(DecompressLiteralsError::ExtraPadding { skipped_bits });
-            }
-            decoder.init_state(&mut br)?;
-
-            while br.bits_remaining() > -(scratch.table.max_num_bits as isize) {
-                target.push(decoder.decode_symbol());
-                decoder.next_state(&mut br)?;
-            }
-            if br.bits_remaining() != -(scratch.table.max_num_bits as isize) {
-                return Err(DecompressLiteralsError::BitstreamReadMismatch {
-                    read_til: br.bits_remaining(),
-                    expected: -(scratch.table.max_num_bits as isize),
-                });
-            }
+        // the first three streams each hold a quarter of the literals (rounded up), the last one the rest
+        let regenerated_size = section.regenerated_size as usize;
+        let segment_size = regenerated_size.div_ceil(4);
+        let last_segment_size = regenerated_size.checked_sub(3 * segment_size).ok_or(
+            err::DecodedLiteralCountMismatch {
+                decoded: 3 * segment_size,
+                expected: regenerated_size,
+            },
+        )?;
+        let streams = [stream1, stream2, stream3, stream4];
+        let stream_starts = [0, jump1, jump2, jump3];
+        for (idx, stream) in streams.iter().enumerate() {
+            *position = bytes_read + stream_starts[idx] as u32;
+            let expected = if idx == 3 {
+                last_segment_size
+            } else {
+                segment_size
+            };
+            decode_stream(&scratch.table, stream, target, idx as u8 + 1, expected)?;
         }
 
         bytes_read += source.len() as u32;
     } else {
         //just decode the one stream
         assert!(num_streams == 1);
-        let mut decoder = HuffmanDecoder::new(&scratch.table);
-        let mut br = BitReaderReversed::new(source);
-        let mut skipped_bits = 0;
-        loop {
-            let val = br.get_bits(1)?;
-            skipped_bits += 1;
-            if val == 1 || skipped_bits > 8 {
-                break;
-            }
-        }
-        if skipped_bits > 8 {
-            //if more than 7 bits are 0, this is not the correct end of the bitstream. Either a bug or corrupted data
-            return Err(DecompressLiteralsError::ExtraPadding { skipped_bits });
-        }
-        decoder.init_state(&mut br)?;
-        while br.bits_remaining() > -(scratch.table.max_num_bits as isize) {
-            target.push(decoder.decode_symbol());
-            decoder.next_state(&mut br)?;
-        }
+        decode_stream(
+            &scratch.table,
+            source,
+            target,
+            1,
+            section.regenerated_size as usize,
+        )?;
         bytes_read += source.len() as u32;
     }
 
@@ -257,3 +277,54 @@ fn decompress_literals(
 
     Ok(bytes_read)
 }
+
+/// Decode a single huffman coded stream into `target`.
+///
+/// The stream has to contain exactly `expected` literals and has to end exactly at the first bit of the stream,
+/// otherwise the stream boundaries are misaligned (or the data is corrupted) and an error is returned.
+fn decode_stream(
+    table: &HuffmanTable,
+    stream: &[u8],
+    target: &mut Vec<u8>,
+    stream_idx: u8,
+    expected: usize,
+) -> Result<(), DecompressLiteralsError> {
+    let start = target.len();
+    let mut decoder = HuffmanDecoder::new(table);
+    let mut br = BitReaderReversed::new(stream);
+    //skip the 0 padding at the end of the last byte of the bit stream and throw away the first 1 found
+    let mut skipped_bits = 0;
+    loop {
+        let val = br.get_bits(1)?;
+        skipped_bits += 1;
+        if val == 1 || skipped_bits > 8 {
+            break;
+        }
+    }
+    if skipped_bits > 8 {
+        //if more than 7 bits are 0, this is not the correct end of the bitstream. Either a bug or corrupted data
+        return Err(DecompressLiteralsError::ExtraPadding { skipped_bits });
+    }
+    decoder.init_state(&mut br)?;
+
+    while br.bits_remaining() > -(table.max_num_bits as isize) {
+        target.push(decoder.decode_symbol());
+        decoder.next_state(&mut br)?;
+    }
+    if br.bits_remaining() != -(table.max_num_bits as isize) {
+        return Err(DecompressLiteralsError::BitstreamReadMismatch {
+            read_til: br.bits_remaining(),
+            expected: -(table.max_num_bits as isize),
+        });
+    }
+
+    let decoded = target.len() - start;
+    if decoded != expected {
+        return Err(DecompressLiteralsError::StreamLiteralCountMismatch {
+            stream: stream_idx,
+            decoded,
+            expected,
+        });
+    }
+    Ok(())
+}
@@ -8,7 +8,6 @@ pub mod decodebuffer;
 pub mod dictionary;
 pub mod literals_section_decoder;
 mod ringbuffer;
-#[allow(dead_code)]
 pub mod scratch;
 pub mod sequence_execution;
 pub mod sequence_section_decoder;
@@ -1,4 +1,5 @@
-use alloc::alloc::{alloc, dealloc};
+use crate::io::{Error, Read};
+use alloc::alloc::{alloc_zeroed, dealloc};
 use core::{alloc::Layout, ptr::NonNull, slice};
 
 pub struct RingBuffer {
@@ -13,6 +14,7 @@ pub struct RingBuffer {
     // 3. `head` and `tail` are in bounds (≥ 0 and < cap)
     // 4. `tail` is never `cap` except for a full buffer, and instead uses the value `0`. In other words, `tail` always points to the place
     //    where the next element would go (if there is space)
+    // 5. The whole allocation is initialized (it is zeroed when allocated), so the free zones can be handed out as slices
     buf: NonNull<u8>,
     cap: usize,
     head: usize,
@@ -49,6 +51,62 @@ impl RingBuffer {
         (x + y).saturating_sub(1)
     }
 
+    /// Return how many bytes the buffer can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.cap.saturating_sub(1)
+    }
+
+    /// Shrink the allocation so it can hold at least `min_capacity` bytes and all bytes currently in the buffer.
+    /// Does nothing if the buffer is not bigger than that anyway.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let needed = usize::max(min_capacity, self.len());
+        if needed >= self.capacity() {
+            return;
+        }
+
+        // SAFETY: if we were succesfully able to construct this layout when we allocated then it's also valid do so now
+        let current_layout = unsafe { Layout::array::<u8>(self.cap).unwrap_unchecked() };
+
+        if needed == 0 {
+            unsafe { dealloc(self.buf.as_ptr(), current_layout) };
+            // SAFETY: Upholds invariants 1-4, the same as an empty buffer created by new()
+            self.buf = NonNull::dangling();
+            self.cap = 0;
+            self.head = 0;
+            self.tail = 0;
+            return;
+        }
+
+        // Always have at least 1 unused element as the sentinel.
+        let new_cap = needed + 1;
+        let new_layout = Layout::array::<u8>(new_cap)
+            .unwrap_or_else(|_| panic!("Could not create layout for u8 array of size {}", new_cap));
+
+        let new_buf = unsafe {
+            // SAFETY: Upholds invariant 5
+            let new_buf = alloc_zeroed(new_layout);
+
+            NonNull::new(new_buf).expect("Allocating new space for the ringbuffer failed")
+        };
+
+        let ((s1_ptr, s1_len), (s2_ptr, s2_len)) = self.data_slice_parts();
+        unsafe {
+            // SAFETY: Upholds invariant 2, we end up populating (0..(len₁ + len₂)) which fits because needed >= len
+            new_buf.as_ptr().copy_from_nonoverlapping(s1_ptr, s1_len);
+            new_buf
+                .as_ptr()
+                .add(s1_len)
+                .copy_from_nonoverlapping(s2_ptr, s2_len);
+            dealloc(self.buf.as_ptr(), current_layout);
+        }
+
+        // SAFETY: Upholds invariants 1 and 3, len < new_cap so tail is in bounds
+        self.buf = new_buf;
+        self.cap = new_cap;
+        self.head = 0;
+        self.tail = s1_len + s2_len;
+    }
+
     /// Empty the buffer and reset the head and tail.
     pub fn clear(&mut self) {
         // SAFETY: Upholds invariant 2, trivially
@@ -97,7 +155,8 @@ impl RingBuffer {
         // alloc the new memory region and panic if alloc fails
         // TODO maybe rework this to generate an error?
         let new_buf = unsafe {
-            let new_buf = alloc(new_layout);
+            // SAFETY: Upholds invariant 5
+            let new_buf = alloc_zeroed(new_layout);
 
             NonNull::new(new_buf).expect("Allocating new space for the ringbuffer failed")
         };
@@ -159,7 +218,7 @@ impl RingBuffer {
 
         self.reserve(len);
 
-        debug_assert!(self.len() + len <= self.cap - 1);
+        debug_assert!(self.len() + len < self.cap);
         debug_assert!(self.free() >= len, "free: {} len: {}", self.free(), len);
 
         let ((f1_ptr, f1_len), (f2_ptr, f2_len)) = self.free_slice_parts();
@@ -185,6 +244,38 @@ impl RingBuffer {
         self.tail = (self.tail + len) % self.cap;
     }
 
+    /// Read exactly `len` bytes from the source and append them to the end of `self`
+    /// without copying them through an intermediate buffer.
+    ///
+    /// If an error is returned nothing has been appended.
+    pub fn extend_from_reader(&mut self, mut source: impl Read, len: usize) -> Result<(), Error> {
+        if len == 0 {
+            return Ok(());
+        }
+
+        self.reserve(len);
+
+        debug_assert!(self.free() >= len, "free: {} len: {}", self.free(), len);
+
+        let ((f1_ptr, f1_len), (f2_ptr, f2_len)) = self.free_slice_parts();
+        debug_assert!(f1_len + f2_len >= len, "{} + {} < {}", f1_len, f2_len, len);
+
+        let in_f1 = usize::min(len, f1_len);
+        let in_f2 = len - in_f1;
+
+        // SAFETY: The free zones are initialized (invariant 5), in bounds and do not overlap with the data.
+        // `tail` is only advanced after all `len` bytes have been written, upholding invariant 2
+        unsafe {
+            source.read_exact(slice::from_raw_parts_mut(f1_ptr, in_f1))?;
+            if in_f2 > 0 {
+                source.read_exact(slice::from_raw_parts_mut(f2_ptr, in_f2))?;
+            }
+        }
+        // SAFETY: Upholds invariant 3 by wrapping `tail` around.
+        self.tail = (self.tail + len) % self.cap;
+        Ok(())
+    }
+
     /// Advance head past `amount` elements, effectively removing
     /// them from the buffer.
     pub fn drop_first_n(&mut self, amount: usize) {
@@ -682,6 +773,75 @@ mod tests {
         assert_eq!(rb.as_slices().1, b"6789");
     }
 
+    #[test]
+    fn extend_from_reader() {
+        let mut rb = RingBuffer::new();
+
+        rb.reserve(15);
+        assert_eq!(17, rb.cap);
+
+        rb.extend_from_reader(&b"0123456789"[..], 10).unwrap();
+        assert_eq!(rb.as_slices().0, b"0123456789");
+        assert_eq!(rb.as_slices().1, b"");
+
+        // wraps around the end of the buffer
+        rb.drop_first_n(8);
+        rb.extend_from_reader(&b"abcdefghijk"[..], 10).unwrap();
+        assert_eq!(rb.len(), 12);
+        assert_eq!(rb.as_slices().0, b"89abcdefg");
+        assert_eq!(rb.as_slices().1, b"hij");
+
+        // a short source leaves the buffer untouched
+        assert!(rb.extend_from_reader(&b"xyz"[..], 4).is_err());
+        assert_eq!(rb.len(), 12);
+        assert_eq!(rb.as_slices().0, b"89abcdefg");
+        assert_eq!(rb.as_slices().1, b"hij");
+
+        // needs to grow
+        rb.extend_from_reader(&b"0123456789"[..], 10).unwrap();
+        assert_eq!(rb.len(), 22);
+        assert_eq!(rb.as_slices().0, b"89abcdefghij0123456789");
+        assert_eq!(rb.as_slices().1, b"");
+    }
+
+    #[test]
+    fn shrink_to() {
+        let mut rb = RingBuffer::new();
+        rb.shrink_to(0);
+        assert_eq!(rb.capacity(), 0);
+
+        rb.reserve(100);
+        let cap = rb.capacity();
+        assert!(cap >= 100);
+
+        // keeps the data, even if it wraps around
+        rb.extend(&[0u8; 110]);
+        rb.drop_first_n(110);
+        rb.extend(b"0123456789abcdefghijklmnopqrstuvwxyz");
+        assert_ne!(rb.as_slices().1, b"");
+        rb.shrink_to(10);
+        assert_eq!(rb.capacity(), 36);
+        assert_eq!(rb.as_slices().0, b"0123456789abcdefghijklmnopqrstuvwxyz");
+        assert_eq!(rb.as_slices().1, b"");
+
+        // never grows
+        rb.shrink_to(1000);
+        assert_eq!(rb.capacity(), 36);
+
+        rb.drop_first_n(30);
+        rb.shrink_to(20);
+        assert_eq!(rb.capacity(), 20);
+        assert_eq!(rb.as_slices().0, b"uvwxyz");
+        rb.extend(b"0123456789");
+        assert_eq!(rb.len(), 16);
+
+        rb.clear();
+        rb.shrink_to(0);
+        assert_eq!(rb.capacity(), 0);
+        rb.extend(b"0123456789");
+        assert_eq!(rb.as_slices().0, b"0123456789");
+    }
+
     #[test]
     fn edge_cases() {
         // Fill exactly, then empty then fill again
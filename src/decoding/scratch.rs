@@ -1,7 +1,8 @@
 //! Structures that wrap around various decoders to make decoding easier.
 
 use super::super::blocks::sequence_section::Sequence;
-use super::decodebuffer::DecodeBuffer;
+use super::decodebuffer::{DecodeBuffer, MAX_BLOCK_SIZE};
+use super::sequence_section_decoder::{LL_MAX_LOG, ML_MAX_LOG, OF_MAX_LOG};
 use crate::decoding::dictionary::Dictionary;
 use crate::fse::FSETable;
 use crate::huff0::HuffmanTable;
@@ -45,13 +46,36 @@ impl DecoderScratch {
         }
     }
 
+    /// Create a scratch space with all buffers allocated up front. Frames with a window of up to `window_size` bytes
+    /// can then be decoded without any further allocations, as long as the decoded bytes are collected after each block.
+    ///
+    /// Use it with [crate::frame_decoder::FrameDecoder::with_scratch].
+    pub fn with_capacity(window_size: usize) -> DecoderScratch {
+        let mut scratch = DecoderScratch::new(window_size);
+        scratch.buffer.preallocate(window_size + MAX_BLOCK_SIZE);
+        scratch.literals_buffer.reserve(MAX_BLOCK_SIZE);
+        scratch.block_content_buffer.reserve(MAX_BLOCK_SIZE);
+        // every sequence decodes to at least 3 bytes
+        scratch.sequences.reserve(MAX_BLOCK_SIZE / 3);
+        scratch.huf.table.preallocate();
+        scratch.fse.literal_lengths.decode.reserve(1 << LL_MAX_LOG);
+        scratch.fse.match_lengths.decode.reserve(1 << ML_MAX_LOG);
+        scratch.fse.offsets.decode.reserve(1 << OF_MAX_LOG);
+        scratch
+    }
+
     pub fn reset(&mut self, window_size: usize) {
+        self.reset_for_content(window_size, None);
+    }
+
+    /// Like [DecoderScratch::reset], see [DecodeBuffer::reset_for_content]
+    pub fn reset_for_content(&mut self, window_size: usize, content_size: Option<u64>) {
         self.offset_hist = [1, 4, 8];
         self.literals_buffer.clear();
         self.sequences.clear();
         self.block_content_buffer.clear();
 
-        self.buffer.reset(window_size);
+        self.buffer.reset_for_content(window_size, content_size);
 
         self.fse.literal_lengths.reset();
         self.fse.match_lengths.reset();
@@ -63,6 +87,14 @@ impl DecoderScratch {
         self.huf.table.reset();
     }
 
+    /// Release memory that is not needed to keep the current state, see [DecodeBuffer::shrink_to_fit]
+    pub fn shrink_to_fit(&mut self) {
+        self.buffer.shrink_to_fit();
+        self.literals_buffer.shrink_to_fit();
+        self.sequences.shrink_to_fit();
+        self.block_content_buffer.shrink_to_fit();
+    }
+
     pub fn init_from_dict(&mut self, dict: &Dictionary) {
         self.fse.reinit_from(&dict.fse);
         self.huf.table.reinit_from(&dict.huf.table);
@@ -99,6 +99,10 @@ pub fn execute_sequences(scratch: &mut DecoderScratch) -> Result<(), ExecuteSequ
 /// Update the most recently used offsets to reflect the provided offset value, and return the
 /// "actual" offset needed because offsets are not stored in a raw way, some transformations are needed
 /// before you get a functional number.
+///
+/// Offset values 1 to 3 refer to the repeated offsets. If the literal length is 0 they are shifted by one:
+/// 1 and 2 mean the second and third repeated offset and 3 means the first repeated offset minus 1.
+/// A result of 0 is never a valid offset and has to be treated as corruption by the caller.
 fn do_offset_history(offset_value: u32, lit_len: u32, scratch: &mut [u32; 3]) -> u32 {
     let actual_offset = if lit_len > 0 {
         match offset_value {
@@ -111,7 +115,8 @@ fn do_offset_history(offset_value: u32, lit_len: u32, scratch: &mut [u32; 3]) ->
     } else {
         match offset_value {
             1..=2 => scratch[offset_value as usize],
-            3 => scratch[0] - 1,
+            // a repeated offset of 0 (e.g. from a corrupted dictionary) must not underflow
+            3 => scratch[0].saturating_sub(1),
             _ => {
                 //new offset
                 offset_value - 3
@@ -156,3 +161,67 @@ fn do_offset_history(offset_value: u32, lit_len: u32, scratch: &mut [u32; 3]) ->
 
     actual_offset
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{do_offset_history, execute_sequences, ExecuteSequencesError};
+    use crate::blocks::sequence_section::Sequence;
+    use crate::decoding::scratch::DecoderScratch;
+
+    #[test]
+    fn offset_history_with_literals() {
+        let mut hist = [1, 4, 8];
+        assert_eq!(do_offset_history(1, 1, &mut hist), 1);
+        assert_eq!(hist, [1, 4, 8]);
+        assert_eq!(do_offset_history(2, 1, &mut hist), 4);
+        assert_eq!(hist, [4, 1, 8]);
+        assert_eq!(do_offset_history(3, 1, &mut hist), 8);
+        assert_eq!(hist, [8, 4, 1]);
+        // new offset
+        assert_eq!(do_offset_history(103, 1, &mut hist), 100);
+        assert_eq!(hist, [100, 8, 4]);
+    }
+
+    #[test]
+    fn offset_history_without_literals() {
+        let mut hist = [1, 4, 8];
+        assert_eq!(do_offset_history(1, 0, &mut hist), 4);
+        assert_eq!(hist, [4, 1, 8]);
+        assert_eq!(do_offset_history(2, 0, &mut hist), 8);
+        assert_eq!(hist, [8, 4, 1]);
+        assert_eq!(do_offset_history(3, 0, &mut hist), 7);
+        assert_eq!(hist, [7, 8, 4]);
+        // new offset
+        assert_eq!(do_offset_history(103, 0, &mut hist), 100);
+        assert_eq!(hist, [100, 7, 8]);
+
+        // the first repeated offset minus 1 can be 0, which is an invalid offset but must not panic
+        let mut hist = [1, 4, 8];
+        assert_eq!(do_offset_history(3, 0, &mut hist), 0);
+        let mut hist = [0, 4, 8];
+        assert_eq!(do_offset_history(3, 0, &mut hist), 0);
+    }
+
+    #[test]
+    fn literals_over_consumed() {
+        let mut scratch = DecoderScratch::new(1024);
+        scratch.literals_buffer.extend_from_slice(b"abcde");
+        // the second sequence alone fits into the literals, but not after the first one
+        scratch.sequences.push(Sequence {
+            ll: 3,
+            ml: 0,
+            of: 4,
+        });
+        scratch.sequences.push(Sequence {
+            ll: 3,
+            ml: 0,
+            of: 4,
+        });
+        assert!(matches!(
+            execute_sequences(&mut scratch),
+            Err(ExecuteSequencesError::NotEnoughBytesForSequence { wanted: 6, have: 5 })
+        ));
+        // nothing past the literals was copied
+        assert_eq!(scratch.buffer.len(), 3);
+    }
+}
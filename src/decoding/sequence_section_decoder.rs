@@ -105,7 +105,20 @@ pub fn decode_sequences(
     scratch: &mut FSEScratch,
     target: &mut Vec<Sequence>,
 ) -> Result<(), DecodeSequenceError> {
-    let bytes_read = maybe_update_fse_tables(section, source, scratch)?;
+    decode_sequences_with_position(section, source, scratch, target, &mut 0)
+}
+
+/// Like [decode_sequences] but keeps `position` at the offset into `source` of the part that is currently decoded
+/// (one of the FSE tables or the bitstream), so errors can be located in the input.
+pub(crate) fn decode_sequences_with_position(
+    section: &SequencesHeader,
+    source: &[u8],
+    scratch: &mut FSEScratch,
+    target: &mut Vec<Sequence>,
+    position: &mut usize,
+) -> Result<(), DecodeSequenceError> {
+    maybe_update_fse_tables(section, source, scratch, position)?;
+    let bytes_read = *position;
 
     vprintln!("Updating tables used {} bytes", bytes_read);
 
@@ -159,18 +172,18 @@ fn decode_sequences_with_rle(
 
     for _seq_idx in 0..section.num_sequences {
         //get the codes from either the RLE byte or from the decoder
-        let ll_code = if scratch.ll_rle.is_some() {
-            scratch.ll_rle.unwrap()
+        let ll_code = if let Some(ll_rle) = scratch.ll_rle {
+            ll_rle
         } else {
             ll_dec.decode_symbol()
         };
-        let ml_code = if scratch.ml_rle.is_some() {
-            scratch.ml_rle.unwrap()
+        let ml_code = if let Some(ml_rle) = scratch.ml_rle {
+            ml_rle
         } else {
             ml_dec.decode_symbol()
         };
-        let of_code = if scratch.of_rle.is_some() {
-            scratch.of_rle.unwrap()
+        let of_code = if let Some(of_rle) = scratch.of_rle {
+            of_rle
         } else {
             of_dec.decode_symbol()
         };
@@ -383,17 +396,18 @@ fn maybe_update_fse_tables(
     section: &SequencesHeader,
     source: &[u8],
     scratch: &mut FSEScratch,
-) -> Result<usize, DecodeSequenceError> {
+    bytes_read: &mut usize,
+) -> Result<(), DecodeSequenceError> {
     let modes = section
         .modes
         .ok_or(DecodeSequenceError::MissingCompressionMode)?;
 
-    let mut bytes_read = 0;
+    *bytes_read = 0;
 
     match modes.ll_mode() {
         ModeType::FSECompressed => {
             let bytes = scratch.literal_lengths.build_decoder(source, LL_MAX_LOG)?;
-            bytes_read += bytes;
+            *bytes_read += bytes;
 
             vprintln!("Updating ll table");
             vprintln!("Used bytes: {}", bytes);
@@ -404,14 +418,14 @@ fn maybe_update_fse_tables(
             if source.is_empty() {
                 return Err(DecodeSequenceError::MissingByteForRleLlTable);
             }
-            bytes_read += 1;
+            *bytes_read += 1;
             scratch.ll_rle = Some(source[0]);
         }
         ModeType::Predefined => {
             vprintln!("Use predefined ll table");
             scratch.literal_lengths.build_from_probabilities(
                 LL_DEFAULT_ACC_LOG,
-                &Vec::from(&LITERALS_LENGTH_DEFAULT_DISTRIBUTION[..]),
+                &LITERALS_LENGTH_DEFAULT_DISTRIBUTION,
             )?;
             scratch.ll_rle = None;
         }
@@ -421,14 +435,14 @@ fn maybe_update_fse_tables(
         }
     };
 
-    let of_source = &source[bytes_read..];
+    let of_source = &source[*bytes_read..];
 
     match modes.of_mode() {
         ModeType::FSECompressed => {
             let bytes = scratch.offsets.build_decoder(of_source, OF_MAX_LOG)?;
             vprintln!("Updating of table");
             vprintln!("Used bytes: {}", bytes);
-            bytes_read += bytes;
+            *bytes_read += bytes;
             scratch.of_rle = None;
         }
         ModeType::RLE => {
@@ -436,15 +450,14 @@ fn maybe_update_fse_tables(
             if of_source.is_empty() {
                 return Err(DecodeSequenceError::MissingByteForRleOfTable);
             }
-            bytes_read += 1;
+            *bytes_read += 1;
             scratch.of_rle = Some(of_source[0]);
         }
         ModeType::Predefined => {
             vprintln!("Use predefined of table");
-            scratch.offsets.build_from_probabilities(
-                OF_DEFAULT_ACC_LOG,
-                &Vec::from(&OFFSET_DEFAULT_DISTRIBUTION[..]),
-            )?;
+            scratch
+                .offsets
+                .build_from_probabilities(OF_DEFAULT_ACC_LOG, &OFFSET_DEFAULT_DISTRIBUTION)?;
             scratch.of_rle = None;
         }
         ModeType::Repeat => {
@@ -453,12 +466,12 @@ fn maybe_update_fse_tables(
         }
     };
 
-    let ml_source = &source[bytes_read..];
+    let ml_source = &source[*bytes_read..];
 
     match modes.ml_mode() {
         ModeType::FSECompressed => {
             let bytes = scratch.match_lengths.build_decoder(ml_source, ML_MAX_LOG)?;
-            bytes_read += bytes;
+            *bytes_read += bytes;
             vprintln!("Updating ml table");
             vprintln!("Used bytes: {}", bytes);
             scratch.ml_rle = None;
@@ -468,15 +481,14 @@ fn maybe_update_fse_tables(
             if ml_source.is_empty() {
                 return Err(DecodeSequenceError::MissingByteForRleMlTable);
             }
-            bytes_read += 1;
+            *bytes_read += 1;
             scratch.ml_rle = Some(ml_source[0]);
         }
         ModeType::Predefined => {
             vprintln!("Use predefined ml table");
-            scratch.match_lengths.build_from_probabilities(
-                ML_DEFAULT_ACC_LOG,
-                &Vec::from(&MATCH_LENGTH_DEFAULT_DISTRIBUTION[..]),
-            )?;
+            scratch
+                .match_lengths
+                .build_from_probabilities(ML_DEFAULT_ACC_LOG, &MATCH_LENGTH_DEFAULT_DISTRIBUTION)?;
             scratch.ml_rle = None;
         }
         ModeType::Repeat => {
@@ -485,7 +497,7 @@ fn maybe_update_fse_tables(
         }
     };
 
-    Ok(bytes_read)
+    Ok(())
 }
 
 // The default Literal Length decoding table uses an accuracy logarithm of 6 bits.
@@ -524,10 +536,7 @@ const OFFSET_DEFAULT_DISTRIBUTION: [i32; 29] = [
 fn test_ll_default() {
     let mut table = crate::fse::FSETable::new();
     table
-        .build_from_probabilities(
-            LL_DEFAULT_ACC_LOG,
-            &Vec::from(&LITERALS_LENGTH_DEFAULT_DISTRIBUTION[..]),
-        )
+        .build_from_probabilities(LL_DEFAULT_ACC_LOG, &LITERALS_LENGTH_DEFAULT_DISTRIBUTION)
         .unwrap();
 
     #[cfg(feature = "std")]
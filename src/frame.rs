@@ -1,10 +1,17 @@
+use crate::decoding::block_decoder::{self, BlockHeaderReadError};
 use crate::io::{Error, Read};
+use core::convert::TryInto;
 use core::fmt;
+use core::ops::Range;
 #[cfg(feature = "std")]
 use std::error::Error as StdError;
 
 /// This magic number is included at the start of a single Zstandard frame
 pub const MAGIC_NUM: u32 = 0xFD2F_B528;
+/// Skippable frames start with a magic number between this one and [SKIPPABLE_MAGIC_NUM_MAX]
+pub const SKIPPABLE_MAGIC_NUM_MIN: u32 = 0x184D_2A50;
+/// Skippable frames start with a magic number between [SKIPPABLE_MAGIC_NUM_MIN] and this one
+pub const SKIPPABLE_MAGIC_NUM_MAX: u32 = 0x184D_2A5F;
 /// The minimum window size is defined as 1 KB
 pub const MIN_WINDOW_SIZE: u64 = 1024;
 /// The maximum window size is 3.75TB
@@ -24,6 +31,7 @@ pub struct Frame {
 }
 
 /// A frame header has a variable size, with a minimum of 2 bytes, and a maximum of 14 bytes.
+#[derive(Debug)]
 pub struct FrameHeader {
     pub descriptor: FrameDescriptor,
     /// The `Window_Descriptor` field contains the minimum size of a memory buffer needed to
@@ -51,6 +59,7 @@ pub struct FrameHeader {
 
 /// The first byte is called the `Frame Header Descriptor`, and it describes what other fields
 /// are present.
+#[derive(Debug)]
 pub struct FrameDescriptor(u8);
 
 #[derive(Debug)]
@@ -322,6 +331,70 @@ impl From<FrameDescriptorError> for ReadFrameHeaderError {
     }
 }
 
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SkippableFrameError {
+    InvalidMagicVariant { got: u8 },
+    UserDataTooBig { got: usize },
+}
+
+impl fmt::Display for SkippableFrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagicVariant { got } => write!(
+                f,
+                "Invalid magic variant for a skippable frame. Is: {}, Should be one of 0..=15",
+                got
+            ),
+            Self::UserDataTooBig { got } => write!(
+                f,
+                "User data does not fit into a skippable frame. Is: {} bytes, Max: {} bytes",
+                got,
+                u32::MAX
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl StdError for SkippableFrameError {}
+
+/// Creates a skippable frame containing the user data. Decoders skip over these frames, so they can be used
+/// to put metadata in between regular frames.
+///
+/// The `magic_variant` (0..=15) is added to [SKIPPABLE_MAGIC_NUM_MIN] to form the magic number of the frame.
+pub fn encode_skippable_frame(
+    magic_variant: u8,
+    user_data: &[u8],
+) -> Result<alloc::vec::Vec<u8>, SkippableFrameError> {
+    if u32::from(magic_variant) > SKIPPABLE_MAGIC_NUM_MAX - SKIPPABLE_MAGIC_NUM_MIN {
+        return Err(SkippableFrameError::InvalidMagicVariant { got: magic_variant });
+    }
+    let len: u32 = user_data
+        .len()
+        .try_into()
+        .map_err(|_| SkippableFrameError::UserDataTooBig {
+            got: user_data.len(),
+        })?;
+
+    let mut frame = alloc::vec::Vec::with_capacity(8 + user_data.len());
+    frame.extend_from_slice(&(SKIPPABLE_MAGIC_NUM_MIN + u32::from(magic_variant)).to_le_bytes());
+    frame.extend_from_slice(&len.to_le_bytes());
+    frame.extend_from_slice(user_data);
+    Ok(frame)
+}
+
+/// Checks if the data starts with the magic number of a Zstandard frame or a skippable frame.
+/// Only the first 4 bytes are looked at, this does not mean that the rest of the data is valid.
+pub fn is_zstd_magic(data: &[u8]) -> bool {
+    let Some(magic_num) = data.get(..4) else {
+        return false;
+    };
+    let magic_num = u32::from_le_bytes([magic_num[0], magic_num[1], magic_num[2], magic_num[3]]);
+    magic_num == MAGIC_NUM
+        || (SKIPPABLE_MAGIC_NUM_MIN..=SKIPPABLE_MAGIC_NUM_MAX).contains(&magic_num)
+}
+
 /// Read a single serialized frame from the reader and return a tuple containing the parsed frame and the number of bytes read.
 pub fn read_frame_header(mut r: impl Read) -> Result<(Frame, u8), ReadFrameHeaderError> {
     use ReadFrameHeaderError as err;
@@ -332,7 +405,7 @@ pub fn read_frame_header(mut r: impl Read) -> Result<(Frame, u8), ReadFrameHeade
     let magic_num = u32::from_le_bytes(buf);
 
     // Skippable frames have a magic number in this interval
-    if (0x184D2A50..=0x184D2A5F).contains(&magic_num) {
+    if (SKIPPABLE_MAGIC_NUM_MIN..=SKIPPABLE_MAGIC_NUM_MAX).contains(&magic_num) {
         r.read_exact(&mut buf)
             .map_err(err::FrameDescriptorReadError)?;
         let skip_size = u32::from_le_bytes(buf);
@@ -407,3 +480,180 @@ pub fn read_frame_header(mut r: impl Read) -> Result<(Frame, u8), ReadFrameHeade
 
     Ok((frame, bytes_read as u8))
 }
+
+/// Parse the frame header at the start of `data` and return the id of the dictionary the frame needs, if any.
+///
+/// This allows checking whether the right dictionary is available before decoding the frame.
+/// A frame that declares the dictionary id 0 does not need a dictionary, so `None` is returned for it.
+pub fn requires_dictionary(data: &[u8]) -> Result<Option<u32>, ReadFrameHeaderError> {
+    let (frame, _) = read_frame_header(data)?;
+    Ok(frame.header.dictionary_id())
+}
+
+/// Where a frame is located in the input of [frames] and what it is
+#[derive(Debug)]
+pub struct FrameSpan {
+    /// The bytes of the input that make up the whole frame, including its header and checksum
+    pub range: Range<usize>,
+    pub kind: FrameKind,
+}
+
+/// What kind of frame a [FrameSpan] is
+#[derive(Debug)]
+pub enum FrameKind {
+    /// A Zstandard frame with the given header that consists of `blocks` blocks
+    Zstd { header: FrameHeader, blocks: usize },
+    /// A skippable frame with the given magic number
+    Skippable { magic_number: u32 },
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FrameSpanError {
+    ReadFrameHeaderError(ReadFrameHeaderError),
+    BlockHeaderReadError(BlockHeaderReadError),
+    /// The input ends before the end of the frame
+    NotEnoughBytes {
+        need: usize,
+        have: usize,
+    },
+}
+
+impl fmt::Display for FrameSpanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadFrameHeaderError(e) => write!(f, "{}", e),
+            Self::BlockHeaderReadError(e) => write!(f, "{}", e),
+            Self::NotEnoughBytes { need, have } => write!(
+                f,
+                "Input ends within the frame. Frame needs at least: {} bytes, Input has: {} bytes",
+                need, have
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl StdError for FrameSpanError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            FrameSpanError::ReadFrameHeaderError(source) => Some(source),
+            FrameSpanError::BlockHeaderReadError(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<ReadFrameHeaderError> for FrameSpanError {
+    fn from(error: ReadFrameHeaderError) -> Self {
+        Self::ReadFrameHeaderError(error)
+    }
+}
+
+impl From<BlockHeaderReadError> for FrameSpanError {
+    fn from(error: BlockHeaderReadError) -> Self {
+        Self::BlockHeaderReadError(error)
+    }
+}
+
+/// Iterate over the Zstandard and skippable frames in `input` without decoding them.
+///
+/// Only the frame headers and block headers are read to find where each frame ends, so this is a quick way to
+/// index a file of concatenated frames. After the first error the iterator ends.
+///
+/// ```
+/// use ruzstd::frame::{frames, FrameKind};
+///
+/// let mut input = ruzstd::frame::encode_skippable_frame(0, b"metadata").unwrap();
+/// input.extend_from_slice(&[0x28, 0xb5, 0x2f, 0xfd, 0x20, 0x01, 0x09, 0x00, 0x00, b'a']);
+/// let spans: Vec<_> = frames(&input).collect::<Result<_, _>>().unwrap();
+/// assert_eq!(spans[0].range, 0..16);
+/// assert!(matches!(spans[0].kind, FrameKind::Skippable { .. }));
+/// assert_eq!(spans[1].range, 16..26);
+/// assert!(matches!(spans[1].kind, FrameKind::Zstd { blocks: 1, .. }));
+/// ```
+pub fn frames(input: &[u8]) -> Frames<'_> {
+    Frames { input, offset: 0 }
+}
+
+/// Iterator returned by [frames]
+pub struct Frames<'a> {
+    input: &'a [u8],
+    offset: usize,
+}
+
+impl Iterator for Frames<'_> {
+    type Item = Result<FrameSpan, FrameSpanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.input[self.offset..];
+        if rest.is_empty() {
+            return None;
+        }
+        match measure_frame(rest) {
+            Ok((len, kind)) => {
+                let start = self.offset;
+                self.offset += len;
+                Some(Ok(FrameSpan {
+                    range: start..self.offset,
+                    kind,
+                }))
+            }
+            Err(e) => {
+                self.offset = self.input.len();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Walk the headers of the frame at the start of `data` and return how long the frame is
+pub(crate) fn measure_frame(data: &[u8]) -> Result<(usize, FrameKind), FrameSpanError> {
+    let not_enough_bytes = |need: usize| FrameSpanError::NotEnoughBytes {
+        need,
+        have: data.len(),
+    };
+
+    let mut rest = data;
+    let (frame, _) = match read_frame_header(&mut rest) {
+        Ok(frame) => frame,
+        Err(ReadFrameHeaderError::SkipFrame {
+            magic_number,
+            length,
+        }) => {
+            let len = 8 + length as usize;
+            if data.len() < len {
+                return Err(not_enough_bytes(len));
+            }
+            return Ok((len, FrameKind::Skippable { magic_number }));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut block_dec = block_decoder::new();
+    let mut blocks = 0;
+    loop {
+        let (block_header, _) = block_dec.read_block_header(&mut rest)?;
+        let content_size = block_header.content_size as usize;
+        if rest.len() < content_size {
+            return Err(not_enough_bytes(data.len() - rest.len() + content_size));
+        }
+        rest = &rest[content_size..];
+        blocks += 1;
+        if block_header.last_block {
+            break;
+        }
+    }
+    if frame.header.descriptor.content_checksum_flag() {
+        if rest.len() < 4 {
+            return Err(not_enough_bytes(data.len() - rest.len() + 4));
+        }
+        rest = &rest[4..];
+    }
+
+    let kind = FrameKind::Zstd {
+        header: frame.header,
+        blocks,
+    };
+    Ok((data.len() - rest.len(), kind))
+}
@@ -3,6 +3,7 @@
 //! and utilities that can be used to decode a frame.
 
 use super::frame;
+use crate::blocks::block::BlockType;
 use crate::decoding::dictionary::Dictionary;
 use crate::decoding::scratch::DecoderScratch;
 use crate::decoding::{self, dictionary};
@@ -67,6 +68,62 @@ use std::error::Error as StdError;
 pub struct FrameDecoder {
     state: Option<FrameDecoderState>,
     dicts: BTreeMap<u32, Dictionary>,
+    options: FrameDecoderOptions,
+    /// Provided by [FrameDecoder::with_scratch], used once the first frame is started
+    scratch: Option<DecoderScratch>,
+}
+
+/// Settings for a [FrameDecoder]. Start from the defaults and change what you need:
+/// ```
+/// use ruzstd::frame_decoder::{FrameDecoder, FrameDecoderOptions};
+///
+/// let frame_dec = FrameDecoder::with_options(
+///     FrameDecoderOptions::new()
+///         .max_window_size(8 * 1024 * 1024)
+///         .salvage(true),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrameDecoderOptions {
+    max_window_size: u64,
+    salvage: bool,
+    ignore_checksum: bool,
+}
+
+impl Default for FrameDecoderOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameDecoderOptions {
+    /// The default options: a window size of up to 100MB is accepted, salvage mode is off and the checksum is calculated
+    pub fn new() -> FrameDecoderOptions {
+        FrameDecoderOptions {
+            max_window_size: MAX_WINDOW_SIZE,
+            salvage: false,
+            ignore_checksum: false,
+        }
+    }
+
+    /// Frames that require a bigger window than this are rejected with [FrameDecoderError::WindowSizeTooBig]
+    /// before any buffers are allocated for them
+    pub fn max_window_size(mut self, max_window_size: u64) -> FrameDecoderOptions {
+        self.max_window_size = max_window_size;
+        self
+    }
+
+    /// See [FrameDecoder::set_salvage]
+    pub fn salvage(mut self, salvage: bool) -> FrameDecoderOptions {
+        self.salvage = salvage;
+        self
+    }
+
+    /// See [FrameDecoder::set_ignore_checksum]
+    pub fn ignore_checksum(mut self, ignore_checksum: bool) -> FrameDecoderOptions {
+        self.ignore_checksum = ignore_checksum;
+        self
+    }
 }
 
 struct FrameDecoderState {
@@ -77,12 +134,17 @@ struct FrameDecoderState {
     bytes_read_counter: u64,
     check_sum: Option<u32>,
     using_dict: Option<u32>,
+    truncated: bool,
 }
 
+#[non_exhaustive]
 pub enum BlockDecodingStrategy {
     All,
     UptoBlocks(usize),
     UptoBytes(usize),
+    /// Decode blocks until at least this many sequences were decoded. Only compressed blocks contain sequences,
+    /// raw and RLE blocks don't count towards the limit.
+    UptoSequences(usize),
 }
 
 #[derive(Debug)]
@@ -90,7 +152,7 @@ pub enum BlockDecodingStrategy {
 pub enum FrameDecoderError {
     ReadFrameHeaderError(frame::ReadFrameHeaderError),
     FrameHeaderError(frame::FrameHeaderError),
-    WindowSizeTooBig { requested: u64 },
+    WindowSizeTooBig { requested: u64, max: u64 },
     DictionaryDecodeError(dictionary::DictionaryDecodeError),
     FailedToReadBlockHeader(decoding::block_decoder::BlockHeaderReadError),
     FailedToReadBlockBody(decoding::block_decoder::DecodeBlockContentError),
@@ -100,6 +162,10 @@ pub enum FrameDecoderError {
     FailedToDrainDecodebuffer(Error),
     TargetTooSmall,
     DictNotProvided { dict_id: u32 },
+    FailedToSkipFrame(Error),
+    ChecksumMismatch { from_data: u32, calculated: u32 },
+    ContentSizeMismatch { declared: u64, decoded: u64 },
+    FrameNotFinished,
 }
 
 #[cfg(feature = "std")]
@@ -114,6 +180,7 @@ impl StdError for FrameDecoderError {
             FrameDecoderError::FailedToReadChecksum(source) => Some(source),
             FrameDecoderError::FailedToInitialize(source) => Some(source),
             FrameDecoderError::FailedToDrainDecodebuffer(source) => Some(source),
+            FrameDecoderError::FailedToSkipFrame(source) => Some(source),
             _ => None,
         }
     }
@@ -128,11 +195,11 @@ impl core::fmt::Display for FrameDecoderError {
             FrameDecoderError::FrameHeaderError(e) => {
                 write!(f, "{:?}", e)
             }
-            FrameDecoderError::WindowSizeTooBig { requested } => {
+            FrameDecoderError::WindowSizeTooBig { requested, max } => {
                 write!(
                     f,
                     "Specified window_size is too big; Requested: {}, Max: {}",
-                    requested, MAX_WINDOW_SIZE,
+                    requested, max,
                 )
             }
             FrameDecoderError::DictionaryDecodeError(e) => {
@@ -166,6 +233,29 @@ impl core::fmt::Display for FrameDecoderError {
             FrameDecoderError::DictNotProvided { dict_id } => {
                 write!(f, "Frame header specified dictionary id 0x{:X} that wasnt provided by add_dict() or reset_with_dict()", dict_id)
             }
+            FrameDecoderError::FailedToSkipFrame(e) => {
+                write!(f, "Failed to skip over a skippable frame: {}", e)
+            }
+            FrameDecoderError::ChecksumMismatch {
+                from_data,
+                calculated,
+            } => {
+                write!(
+                    f,
+                    "Checksum of the frame did not match the decoded content. From data: 0x{:X}, calculated: 0x{:X}",
+                    from_data, calculated,
+                )
+            }
+            FrameDecoderError::ContentSizeMismatch { declared, decoded } => {
+                write!(
+                    f,
+                    "Frame header declared a content size of {} bytes but {} bytes were decoded",
+                    declared, decoded,
+                )
+            }
+            FrameDecoderError::FrameNotFinished => {
+                write!(f, "The current frame has not been decoded completely")
+            }
         }
     }
 }
@@ -194,42 +284,174 @@ impl From<frame::ReadFrameHeaderError> for FrameDecoderError {
     }
 }
 
+impl FrameDecoderError {
+    /// Whether this error was caused by the source running out of bytes
+    fn is_unexpected_eof(&self) -> bool {
+        use crate::io::ErrorKind;
+        match self {
+            FrameDecoderError::FailedToReadBlockHeader(
+                decoding::block_decoder::BlockHeaderReadError::ReadError(e),
+            ) => e.kind() == ErrorKind::UnexpectedEof,
+            FrameDecoderError::FailedToReadBlockBody(
+                decoding::block_decoder::DecodeBlockContentError::ReadError { source, .. },
+            ) => source.kind() == ErrorKind::UnexpectedEof,
+            FrameDecoderError::FailedToReadBlockBody(
+                decoding::block_decoder::DecodeBlockContentError::DecompressBlockError {
+                    source: decoding::block_decoder::DecompressBlockError::BlockContentReadError(e),
+                    ..
+                },
+            ) => e.kind() == ErrorKind::UnexpectedEof,
+            FrameDecoderError::FailedToReadChecksum(e) => e.kind() == ErrorKind::UnexpectedEof,
+            _ => false,
+        }
+    }
+}
+
 const MAX_WINDOW_SIZE: u64 = 1024 * 1024 * 100;
 
+fn check_window_size(frame: &frame::Frame, max_window_size: u64) -> Result<u64, FrameDecoderError> {
+    let window_size = frame.header.window_size()?;
+    if window_size > max_window_size {
+        return Err(FrameDecoderError::WindowSizeTooBig {
+            requested: window_size,
+            max: max_window_size,
+        });
+    }
+    Ok(window_size)
+}
+
+/// The content size the frame declares in its header, if it declares one
+fn declared_content_size(frame: &frame::Frame) -> Option<u64> {
+    match frame.header.descriptor.frame_content_size_bytes() {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(frame.header.frame_content_size()),
+    }
+}
+
 impl FrameDecoderState {
-    pub fn new(source: impl Read) -> Result<FrameDecoderState, FrameDecoderError> {
+    /// Uses the provided scratch if there is one, else a new one is allocated
+    pub fn new(
+        source: impl Read,
+        max_window_size: u64,
+        scratch: &mut Option<DecoderScratch>,
+    ) -> Result<FrameDecoderState, FrameDecoderError> {
         let (frame, header_size) = frame::read_frame_header(source)?;
-        let window_size = frame.header.window_size()?;
+        let window_size = check_window_size(&frame, max_window_size)?;
+        let decoder_scratch = match scratch.take() {
+            Some(mut scratch) => {
+                scratch.reset_for_content(window_size as usize, declared_content_size(&frame));
+                scratch
+            }
+            None => DecoderScratch::new(window_size as usize),
+        };
         Ok(FrameDecoderState {
             frame,
             frame_finished: false,
             block_counter: 0,
-            decoder_scratch: DecoderScratch::new(window_size as usize),
+            decoder_scratch,
             bytes_read_counter: u64::from(header_size),
             check_sum: None,
             using_dict: None,
+            truncated: false,
         })
     }
 
-    pub fn reset(&mut self, source: impl Read) -> Result<(), FrameDecoderError> {
+    pub fn reset(
+        &mut self,
+        source: impl Read,
+        max_window_size: u64,
+    ) -> Result<(), FrameDecoderError> {
         let (frame, header_size) = frame::read_frame_header(source)?;
-        let window_size = frame.header.window_size()?;
-
-        if window_size > MAX_WINDOW_SIZE {
-            return Err(FrameDecoderError::WindowSizeTooBig {
-                requested: window_size,
-            });
-        }
+        let window_size = check_window_size(&frame, max_window_size)?;
 
+        self.decoder_scratch
+            .reset_for_content(window_size as usize, declared_content_size(&frame));
         self.frame = frame;
         self.frame_finished = false;
         self.block_counter = 0;
-        self.decoder_scratch.reset(window_size as usize);
         self.bytes_read_counter = u64::from(header_size);
         self.check_sum = None;
         self.using_dict = None;
+        self.truncated = false;
         Ok(())
     }
+
+    fn decode_blocks(
+        &mut self,
+        mut source: impl Read,
+        strat: BlockDecodingStrategy,
+    ) -> Result<bool, FrameDecoderError> {
+        use FrameDecoderError as err;
+        let mut block_dec = decoding::block_decoder::new();
+
+        let buffer_size_before = self.decoder_scratch.buffer.len();
+        let block_counter_before = self.block_counter;
+        let mut sequence_counter = 0;
+        loop {
+            vprintln!("################");
+            vprintln!("Next Block: {}", self.block_counter);
+            vprintln!("################");
+            let (block_header, block_header_size) = block_dec
+                .read_block_header(&mut source)
+                .map_err(err::FailedToReadBlockHeader)?;
+            self.bytes_read_counter += u64::from(block_header_size);
+
+            vprintln!();
+            vprintln!(
+                "Found {} block with size: {}, which will be of size: {}",
+                block_header.block_type,
+                block_header.content_size,
+                block_header.decompressed_size
+            );
+
+            let bytes_read_in_block_body = block_dec
+                .decode_block_content(&block_header, &mut self.decoder_scratch, &mut source)
+                .map_err(|e| err::FailedToReadBlockBody(e.offset_by(self.bytes_read_counter)))?;
+            self.bytes_read_counter += bytes_read_in_block_body;
+
+            self.block_counter += 1;
+            if block_header.block_type == BlockType::Compressed {
+                sequence_counter += self.decoder_scratch.sequences.len();
+            }
+
+            vprintln!("Output: {}", self.decoder_scratch.buffer.len());
+
+            if block_header.last_block {
+                self.frame_finished = true;
+                if self.frame.header.descriptor.content_checksum_flag() {
+                    let mut chksum = [0u8; 4];
+                    source
+                        .read_exact(&mut chksum)
+                        .map_err(err::FailedToReadChecksum)?;
+                    self.bytes_read_counter += 4;
+                    let chksum = u32::from_le_bytes(chksum);
+                    self.check_sum = Some(chksum);
+                }
+                break;
+            }
+
+            match strat {
+                BlockDecodingStrategy::All => { /* keep going */ }
+                BlockDecodingStrategy::UptoBlocks(n) => {
+                    if self.block_counter - block_counter_before >= n {
+                        break;
+                    }
+                }
+                BlockDecodingStrategy::UptoBytes(n) => {
+                    if self.decoder_scratch.buffer.len() - buffer_size_before >= n {
+                        break;
+                    }
+                }
+                BlockDecodingStrategy::UptoSequences(n) => {
+                    if sequence_counter >= n {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(self.frame_finished)
+    }
 }
 
 impl Default for FrameDecoder {
@@ -246,9 +468,54 @@ impl FrameDecoder {
         FrameDecoder {
             state: None,
             dicts: BTreeMap::new(),
+            options: FrameDecoderOptions::new(),
+            scratch: None,
+        }
+    }
+
+    /// Like [FrameDecoder::new] but with non-default [FrameDecoderOptions]
+    pub fn with_options(options: FrameDecoderOptions) -> FrameDecoder {
+        FrameDecoder {
+            state: None,
+            dicts: BTreeMap::new(),
+            options,
+            scratch: None,
+        }
+    }
+
+    /// Like [FrameDecoder::new] but all frames are decoded with the provided scratch space instead of allocating one.
+    ///
+    /// With a scratch from [DecoderScratch::with_capacity] frames can be decoded without any allocations, which is useful
+    /// where allocating during decoding is not allowed. Only collect the decoded bytes with functions that do not return
+    /// a `Vec`, e.g. [FrameDecoder::collect_to_writer], [FrameDecoder::read] or [FrameDecoder::decode_from_to].
+    pub fn with_scratch(scratch: DecoderScratch) -> FrameDecoder {
+        FrameDecoder {
+            state: None,
+            dicts: BTreeMap::new(),
+            options: FrameDecoderOptions::new(),
+            scratch: Some(scratch),
         }
     }
 
+    /// In salvage mode a source that ends in the middle of a block (or before the checksum) does not cause an error.
+    /// Instead the frame is treated as finished after the last completely decoded block, so everything
+    /// decoded up to that point can still be collected. Use [FrameDecoder::is_truncated] to check if that happened.
+    ///
+    /// This is off by default. Only running out of input is salvaged, corrupted blocks still return an error.
+    pub fn set_salvage(&mut self, salvage: bool) {
+        self.options.salvage = salvage;
+    }
+
+    /// If set, the decoded content is not hashed, which saves a pass over the data when it is trusted anyway.
+    /// The checksum at the end of a frame is still read (so [FrameDecoder::get_checksum_from_data] works)
+    /// but it is not checked in any way, and [FrameDecoder::get_calculated_checksum] returns None.
+    ///
+    /// Takes effect with the next frame, so the checksum of the current frame is either calculated over all of its
+    /// content or not at all.
+    pub fn set_ignore_checksum(&mut self, ignore_checksum: bool) {
+        self.options.ignore_checksum = ignore_checksum;
+    }
+
     /// init() will allocate all needed buffers if it is the first time this decoder is used
     /// else they just reset these buffers with not further allocations
     ///
@@ -269,11 +536,15 @@ impl FrameDecoder {
         use FrameDecoderError as err;
         let state = match &mut self.state {
             Some(s) => {
-                s.reset(source)?;
+                s.reset(source, self.options.max_window_size)?;
                 s
             }
             None => {
-                self.state = Some(FrameDecoderState::new(source)?);
+                self.state = Some(FrameDecoderState::new(
+                    source,
+                    self.options.max_window_size,
+                    &mut self.scratch,
+                )?);
                 self.state.as_mut().unwrap()
             }
         };
@@ -285,9 +556,23 @@ impl FrameDecoder {
             state.decoder_scratch.init_from_dict(dict);
             state.using_dict = Some(dict_id);
         }
+        #[cfg(feature = "hash")]
+        {
+            state.decoder_scratch.buffer.hash_enabled = !self.options.ignore_checksum;
+        }
         Ok(())
     }
 
+    /// Release memory the decoder does not need to keep its current state, e.g. the big buffers left behind
+    /// by a frame with a large window. Bytes that have not been collected yet are kept.
+    ///
+    /// [FrameDecoder::reset] already does this automatically if the buffer is a lot bigger than the next frame needs.
+    pub fn shrink_to_fit(&mut self) {
+        if let Some(state) = &mut self.state {
+            state.decoder_scratch.shrink_to_fit();
+        }
+    }
+
     /// Add a dict to the FrameDecoder that can be used when needed. The FrameDecoder uses the appropriate one dynamically
     pub fn add_dict(&mut self, dict: Dictionary) -> Result<(), FrameDecoderError> {
         self.dicts.insert(dict.id, dict);
@@ -318,6 +603,33 @@ impl FrameDecoder {
         }
     }
 
+    /// Returns the content size the current frame declares in its header, if it declares one.
+    ///
+    /// This is available right after [FrameDecoder::reset]/[FrameDecoder::init] read the header,
+    /// so it can be used to preallocate the output before decoding any blocks.
+    pub fn current_frame_content_size(&self) -> Option<u64> {
+        let state = self.state.as_ref()?;
+        declared_content_size(&state.frame)
+    }
+
+    /// Returns how many bytes of history the decoder keeps to decode the current frame, 0 if there is no frame.
+    ///
+    /// This is the window size from the frame header, which for single segment frames is the content size.
+    /// If the frame declares a content size smaller than its window, only that much is kept, as matches can't
+    /// reach back further than the start of the content. The content of a dictionary the frame uses is kept
+    /// in addition to this.
+    pub fn effective_window_size(&self) -> u64 {
+        let state = match &self.state {
+            None => return 0,
+            Some(s) => s,
+        };
+        let window_size = state.decoder_scratch.buffer.window_size as u64;
+        match declared_content_size(&state.frame) {
+            Some(content_size) if content_size < window_size => content_size,
+            _ => window_size,
+        }
+    }
+
     /// Returns the checksum that was read from the data. Only available after all bytes have been read. It is the last 4 bytes of a zstd-frame
     pub fn get_checksum_from_data(&self) -> Option<u32> {
         let state = match &self.state {
@@ -329,7 +641,8 @@ impl FrameDecoder {
     }
 
     /// Returns the checksum that was calculated while decoding.
-    /// Only a sensible value after all decoded bytes have been collected/read from the FrameDecoder
+    /// Only a sensible value after all decoded bytes have been collected/read from the FrameDecoder.
+    /// Returns None if the checksum is ignored, see [FrameDecoder::set_ignore_checksum]
     #[cfg(feature = "hash")]
     pub fn get_calculated_checksum(&self) -> Option<u32> {
         use core::hash::Hasher;
@@ -338,6 +651,9 @@ impl FrameDecoder {
             None => return None,
             Some(s) => s,
         };
+        if !state.decoder_scratch.buffer.hash_enabled {
+            return None;
+        }
         let cksum_64bit = state.decoder_scratch.buffer.hash.finish();
         //truncate to lower 32bit because reasons...
         Some(cksum_64bit as u32)
@@ -352,6 +668,21 @@ impl FrameDecoder {
         state.bytes_read_counter
     }
 
+    /// Returns how many bytes of the source have been consumed and how many bytes have been decoded
+    /// for the current frame so far, as `(compressed, decompressed)`.
+    ///
+    /// Both counters are updated with each decoded block, the compressed count includes the frame header
+    /// and the block headers. Recording the positions after each block gives a coarse seek table for the frame.
+    pub fn positions(&self) -> (u64, u64) {
+        match &self.state {
+            None => (0, 0),
+            Some(s) => (
+                s.bytes_read_counter,
+                s.decoder_scratch.buffer.total_output(),
+            ),
+        }
+    }
+
     /// Whether the current frames last block has been decoded yet
     /// If this returns true you can call the drain* functions to get all content
     /// (the read() function will drain automatically if this returns true)
@@ -360,13 +691,22 @@ impl FrameDecoder {
             None => return true,
             Some(s) => s,
         };
-        if state.frame.header.descriptor.content_checksum_flag() {
+        if state.frame.header.descriptor.content_checksum_flag() && !state.truncated {
             state.frame_finished && state.check_sum.is_some()
         } else {
             state.frame_finished
         }
     }
 
+    /// Whether the current frame was cut short by the end of the source while decoding in salvage mode.
+    /// See [FrameDecoder::set_salvage]
+    pub fn is_truncated(&self) -> bool {
+        match &self.state {
+            None => false,
+            Some(s) => s.truncated,
+        }
+    }
+
     /// Counter for how many blocks have already been decoded
     pub fn blocks_decoded(&self) -> usize {
         let state = match &self.state {
@@ -382,72 +722,255 @@ impl FrameDecoder {
     /// about that you can just choose the strategy "All" and have all blocks of the frame decoded into the buffer
     pub fn decode_blocks(
         &mut self,
-        mut source: impl Read,
+        source: impl Read,
         strat: BlockDecodingStrategy,
     ) -> Result<bool, FrameDecoderError> {
         use FrameDecoderError as err;
         let state = self.state.as_mut().ok_or(err::NotYetInitialized)?;
 
-        let mut block_dec = decoding::block_decoder::new();
+        match state.decode_blocks(source, strat) {
+            Err(e) if self.options.salvage && e.is_unexpected_eof() => {
+                vprintln!("Source ended early, salvaging decoded blocks: {}", e);
+                state.frame_finished = true;
+                state.truncated = true;
+                Ok(true)
+            }
+            result => result,
+        }
+    }
 
-        let buffer_size_before = state.decoder_scratch.buffer.len();
-        let block_counter_before = state.block_counter;
-        loop {
-            vprintln!("################");
-            vprintln!("Next Block: {}", state.block_counter);
-            vprintln!("################");
-            let (block_header, block_header_size) = block_dec
-                .read_block_header(&mut source)
-                .map_err(err::FailedToReadBlockHeader)?;
-            state.bytes_read_counter += u64::from(block_header_size);
+    /// Decodes the whole frame from the source and hands the decoded bytes to the callback in chunks
+    /// as soon as they are no longer needed for decoding. The full output is never accumulated, the decoder only
+    /// holds on to window_size bytes plus the current block.
+    ///
+    /// The callback may be called many times per frame. This resets the decoder, just like [FrameDecoder::reset].
+    pub fn decode_with(
+        &mut self,
+        mut source: impl Read,
+        callback: impl FnMut(&[u8]),
+    ) -> Result<(), FrameDecoderError> {
+        use FrameDecoderError as err;
+        self.reset(&mut source)?;
 
-            vprintln!();
-            vprintln!(
-                "Found {} block with size: {}, which will be of size: {}",
-                block_header.block_type,
-                block_header.content_size,
-                block_header.decompressed_size
-            );
+        let mut sink = CallbackWriter(callback);
+        while !self.is_finished() {
+            self.decode_blocks(&mut source, BlockDecodingStrategy::UptoBlocks(1))?;
+            self.collect_to_writer(&mut sink)
+                .map_err(err::FailedToDrainDecodebuffer)?;
+        }
+        Ok(())
+    }
 
-            let bytes_read_in_block_body = block_dec
-                .decode_block_content(&block_header, &mut state.decoder_scratch, &mut source)
-                .map_err(err::FailedToReadBlockBody)?;
-            state.bytes_read_counter += bytes_read_in_block_body;
+    /// Decodes the whole frame from the source and feeds the decoded bytes into the hasher, without
+    /// accumulating the full output. Returns the digest of the hasher.
+    ///
+    /// This resets the decoder, just like [FrameDecoder::reset].
+    pub fn decode_and_hash<H: core::hash::Hasher>(
+        &mut self,
+        source: impl Read,
+        hasher: &mut H,
+    ) -> Result<u64, FrameDecoderError> {
+        self.decode_with(source, |chunk| hasher.write(chunk))?;
+        Ok(hasher.finish())
+    }
 
-            state.block_counter += 1;
+    /// Decodes the frame at the start of `input` and appends its content to `output`.
+    /// Returns how many bytes of `input` the frame took up, the bytes after the frame are not looked at.
+    ///
+    /// Raw blocks are copied from `input` to `output` directly as far as later blocks can't reach back into them,
+    /// e.g. the last block, blocks that are bigger than the window or raw blocks followed by another raw block.
+    /// This saves copying them through the decodebuffer, e.g. for mostly raw frames in a memory mapped file.
+    ///
+    /// This resets the decoder, just like [FrameDecoder::reset].
+    pub fn decode_slice_to_vec(
+        &mut self,
+        input: &[u8],
+        output: &mut Vec<u8>,
+    ) -> Result<usize, FrameDecoderError> {
+        use FrameDecoderError as err;
+        let mut source = input;
+        self.reset(&mut source)?;
 
-            vprintln!("Output: {}", state.decoder_scratch.buffer.len());
+        while !self.is_finished() {
+            let state = self.state.as_mut().expect("Was initialized by reset");
+            let mut header_source = source;
+            let Ok((block_header, header_size)) =
+                decoding::block_decoder::new().read_block_header(&mut header_source)
+            else {
+                // let the regular path report the error
+                self.decode_blocks(&mut source, BlockDecodingStrategy::UptoBlocks(1))?;
+                continue;
+            };
+            let checksum_size = if block_header.last_block
+                && state.frame.header.descriptor.content_checksum_flag()
+            {
+                4
+            } else {
+                0
+            };
+            let size = block_header.content_size as usize;
+            // how many bytes at the end of this block later blocks may still reach back to
+            let window_size = state.decoder_scratch.buffer.window_size;
+            let keep = if block_header.last_block {
+                0
+            } else {
+                match header_source
+                    .get(size..)
+                    .map(|mut next| decoding::block_decoder::new().read_block_header(&mut next))
+                {
+                    // a following raw block covers part of the window without any matches
+                    Some(Ok((next_header, _))) if next_header.block_type == BlockType::Raw => {
+                        window_size.saturating_sub(next_header.content_size as usize)
+                    }
+                    _ => window_size,
+                }
+            };
+            let direct = block_header.block_type == BlockType::Raw
+                && header_source.len() >= size + checksum_size
+                && size >= keep;
+            if !direct {
+                self.decode_blocks(&mut source, BlockDecodingStrategy::UptoBlocks(1))?;
+                self.collect_to_writer(CallbackWriter(|chunk: &[u8]| {
+                    output.extend_from_slice(chunk)
+                }))
+                .map_err(err::FailedToDrainDecodebuffer)?;
+                continue;
+            }
 
+            state
+                .decoder_scratch
+                .buffer
+                .push_past_window(&header_source[..size], keep, output);
+            source = &header_source[size..];
+            state.bytes_read_counter += u64::from(header_size) + size as u64;
+            state.block_counter += 1;
             if block_header.last_block {
                 state.frame_finished = true;
-                if state.frame.header.descriptor.content_checksum_flag() {
-                    let mut chksum = [0u8; 4];
-                    source
-                        .read_exact(&mut chksum)
-                        .map_err(err::FailedToReadChecksum)?;
+                if checksum_size != 0 {
+                    let chksum = source[..4].try_into().expect("optimized away");
+                    state.check_sum = Some(u32::from_le_bytes(chksum));
                     state.bytes_read_counter += 4;
-                    let chksum = u32::from_le_bytes(chksum);
-                    state.check_sum = Some(chksum);
+                    source = &source[4..];
                 }
+            }
+        }
+
+        self.collect_to_writer(CallbackWriter(|chunk: &[u8]| {
+            output.extend_from_slice(chunk)
+        }))
+        .map_err(err::FailedToDrainDecodebuffer)?;
+        Ok(input.len() - source.len())
+    }
+
+    /// Decodes the start of the frame at the start of `input` and returns its first `n` bytes, e.g. for a preview.
+    /// Blocks are decoded only until at least `n` bytes were produced, the rest of the frame is not looked at.
+    /// If the frame has fewer than `n` bytes of content all of it is returned.
+    ///
+    /// The checksum of the frame is not checked. This resets the decoder, just like [FrameDecoder::reset].
+    /// If the frame was not decoded completely, the decoder has to be reset before it is used again.
+    pub fn decode_prefix(&mut self, input: &[u8], n: usize) -> Result<Vec<u8>, FrameDecoderError> {
+        use FrameDecoderError as err;
+        let mut source = input;
+        self.reset(&mut source)?;
+
+        let mut output = Vec::new();
+        loop {
+            let state = self.state.as_mut().expect("Was initialized by reset");
+            if state.frame_finished || state.decoder_scratch.buffer.total_output() >= n as u64 {
                 break;
             }
+            self.decode_blocks(&mut source, BlockDecodingStrategy::UptoBlocks(1))?;
+            self.collect_to_writer(CallbackWriter(|chunk: &[u8]| {
+                output.extend_from_slice(chunk)
+            }))
+            .map_err(err::FailedToDrainDecodebuffer)?;
+        }
 
-            match strat {
-                BlockDecodingStrategy::All => { /* keep going */ }
-                BlockDecodingStrategy::UptoBlocks(n) => {
-                    if state.block_counter - block_counter_before >= n {
-                        break;
+        // the bytes that are still held back as the window are needed as well
+        let state = self.state.as_mut().expect("Was initialized by reset");
+        output.extend(state.decoder_scratch.buffer.drain());
+        output.truncate(n);
+        Ok(output)
+    }
+
+    /// Decodes all frames in `input` into memory that is requested from `grow` as it is needed and returns how many
+    /// bytes were written. Skippable frames are skipped.
+    ///
+    /// Whenever there are decoded bytes but no space left, `grow` is called with `output` and the number of bytes that
+    /// could be written right now. It returns the space for the next bytes, which may be smaller or bigger than requested
+    /// but must not be empty. Each returned slice is filled completely before `grow` is called again, only the last one
+    /// may be filled partially. The bytes are copied from the decodebuffer directly into these slices.
+    ///
+    /// This resets the decoder, just like [FrameDecoder::reset].
+    ///
+    /// ```
+    /// # let frame = [0x28, 0xb5, 0x2f, 0xfd, 0x24, 0x01, 0x09, 0x00, 0x00, 0x61, 0x5b, 0x6e, 0x8c, 0xa9];
+    /// let mut frame_dec = ruzstd::FrameDecoder::new();
+    /// let mut output = Vec::new();
+    /// let written = frame_dec
+    ///     .decode_all_cb(&frame, &mut output, |output: &mut Vec<u8>, additional| {
+    ///         let len = output.len();
+    ///         output.resize(len + additional, 0);
+    ///         &mut output[len..]
+    ///     })
+    ///     .unwrap();
+    /// output.truncate(written);
+    /// assert_eq!(output, b"a");
+    /// ```
+    pub fn decode_all_cb<T: ?Sized>(
+        &mut self,
+        mut input: &[u8],
+        output: &mut T,
+        mut grow: impl FnMut(&mut T, usize) -> &mut [u8],
+    ) -> Result<usize, FrameDecoderError> {
+        use FrameDecoderError as err;
+        let mut written = 0;
+        let mut space: &mut [u8] = &mut [];
+
+        while !input.is_empty() {
+            match self.reset(&mut input) {
+                Ok(()) => {}
+                Err(err::ReadFrameHeaderError(frame::ReadFrameHeaderError::SkipFrame {
+                    length,
+                    ..
+                })) => {
+                    let length = length as usize;
+                    if input.len() < length {
+                        return Err(err::FailedToSkipFrame(Error::from(
+                            crate::io::ErrorKind::UnexpectedEof,
+                        )));
                     }
+                    input = &input[length..];
+                    continue;
                 }
-                BlockDecodingStrategy::UptoBytes(n) => {
-                    if state.decoder_scratch.buffer.len() - buffer_size_before >= n {
-                        break;
+                Err(e) => return Err(e),
+            }
+
+            loop {
+                let finished = self.is_finished();
+                if !finished {
+                    self.decode_blocks(&mut input, BlockDecodingStrategy::UptoBlocks(1))?;
+                }
+                while self.can_collect() > 0 {
+                    if space.is_empty() {
+                        space = grow(output, self.can_collect());
+                        if space.is_empty() {
+                            return Err(err::TargetTooSmall);
+                        }
                     }
+                    let chunk = self.collect_slice(space.len());
+                    let len = chunk.len();
+                    space[..len].copy_from_slice(chunk);
+                    self.consume(len);
+                    space = &mut core::mem::take(&mut space)[len..];
+                    written += len;
+                }
+                if finished {
+                    break;
                 }
             }
         }
-
-        Ok(state.frame_finished)
+        Ok(written)
     }
 
     /// Collect bytes and retain window_size bytes while decoding is still going on.
@@ -477,6 +1000,27 @@ impl FrameDecoder {
         }
     }
 
+    /// Borrow up to `max` bytes that can be collected directly from the decodebuffer, without copying them.
+    /// The returned slice may be shorter than [FrameDecoder::can_collect] reports, because the decodebuffer is not necessarily contiguous.
+    ///
+    /// The bytes stay in the decodebuffer until they are acknowledged with [FrameDecoder::consume].
+    pub fn collect_slice(&self, max: usize) -> &[u8] {
+        let collectable = self.can_collect();
+        match &self.state {
+            None => &[],
+            Some(s) => s.decoder_scratch.buffer.peek(usize::min(max, collectable)),
+        }
+    }
+
+    /// Removes `amount` bytes that were returned by [FrameDecoder::collect_slice] from the decodebuffer.
+    /// At most [FrameDecoder::can_collect] bytes are removed.
+    pub fn consume(&mut self, amount: usize) {
+        let amount = usize::min(amount, self.can_collect());
+        if let Some(s) = &mut self.state {
+            s.decoder_scratch.buffer.consume(amount);
+        }
+    }
+
     /// How many bytes can currently be collected from the decodebuffer, while decoding is going on this will be lower than the actual decodbuffer size
     /// because window_size bytes need to be retained for decoding.
     /// After decoding of the frame (is_finished() == true) has finished it will report all remaining bytes
@@ -546,8 +1090,9 @@ impl FrameDecoder {
                         state.bytes_read_counter += 4;
                         let chksum = u32::from_le_bytes(chksum);
                         state.check_sum = Some(chksum);
+                        return Ok((4, 0));
                     }
-                    return Ok((4, 0));
+                    return Ok((0, 0));
                 }
 
                 loop {
@@ -572,7 +1117,9 @@ impl FrameDecoder {
                             &mut state.decoder_scratch,
                             &mut mt_source,
                         )
-                        .map_err(err::FailedToReadBlockBody)?;
+                        .map_err(|e| {
+                            err::FailedToReadBlockBody(e.offset_by(state.bytes_read_counter))
+                        })?;
                     state.bytes_read_counter += bytes_read_in_block_body;
                     state.block_counter += 1;
 
@@ -603,6 +1150,91 @@ impl FrameDecoder {
     }
 }
 
+/// Counts the blocks of the frame at the start of `source` by walking the block headers, without decoding anything.
+/// The whole frame has to be in `source`, including its checksum. See [frame::frames] for the position of each frame.
+///
+/// Skippable frames are not supported and result in a [frame::ReadFrameHeaderError::SkipFrame] error.
+pub fn count_blocks(source: &[u8]) -> Result<usize, frame::FrameSpanError> {
+    match frame::measure_frame(source)? {
+        (_, frame::FrameKind::Zstd { blocks, .. }) => Ok(blocks),
+        (len, frame::FrameKind::Skippable { magic_number }) => {
+            Err(frame::ReadFrameHeaderError::SkipFrame {
+                magic_number,
+                length: (len - 8) as u32,
+            }
+            .into())
+        }
+    }
+}
+
+/// Decodes exactly one frame from the start of `input` and returns its content together with the rest of `input`
+/// that follows the frame.
+///
+/// The trailing bytes are not looked at, so this can be used to decode a frame embedded in some container format.
+/// Skippable frames are not supported and result in a [frame::ReadFrameHeaderError::SkipFrame] error.
+///
+/// ```
+/// # let frame = [0x28, 0xb5, 0x2f, 0xfd, 0x24, 0x01, 0x09, 0x00, 0x00, 0x61, 0x5b, 0x6e, 0x8c, 0xa9];
+/// let mut input = frame.to_vec();
+/// input.extend_from_slice(b"trailer");
+/// let (content, rest) = ruzstd::decode_one_frame(&input).unwrap();
+/// assert_eq!(content, b"a");
+/// assert_eq!(rest, b"trailer");
+/// ```
+pub fn decode_one_frame(input: &[u8]) -> Result<(Vec<u8>, &[u8]), FrameDecoderError> {
+    let mut frame_dec = FrameDecoder::new();
+    let mut content = Vec::new();
+    let frame_len = frame_dec.decode_slice_to_vec(input, &mut content)?;
+    Ok((content, &input[frame_len..]))
+}
+
+/// Decodes the frame at the start of `source` completely but throws the decoded content away.
+/// Returns Ok only if the whole frame could be decoded, and the decoded content matches the content size
+/// and the checksum of the frame if they are present. Bytes after the end of the frame are ignored.
+///
+/// The checksum can only be verified if the `hash` feature is enabled. Frames that need a dictionary
+/// fail with [FrameDecoderError::DictNotProvided].
+pub fn validate_frame(mut source: &[u8]) -> Result<(), FrameDecoderError> {
+    use FrameDecoderError as err;
+    let mut frame_dec = FrameDecoder::new();
+    frame_dec.decode_with(&mut source, |_| {})?;
+
+    let (_, decoded) = frame_dec.positions();
+    if let Some(declared) = frame_dec.current_frame_content_size() {
+        if declared != decoded {
+            return Err(err::ContentSizeMismatch { declared, decoded });
+        }
+    }
+
+    #[cfg(feature = "hash")]
+    if let (Some(from_data), Some(calculated)) = (
+        frame_dec.get_checksum_from_data(),
+        frame_dec.get_calculated_checksum(),
+    ) {
+        if from_data != calculated {
+            return Err(err::ChecksumMismatch {
+                from_data,
+                calculated,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Passes everything written to it on to the callback of [FrameDecoder::decode_with]
+struct CallbackWriter<F: FnMut(&[u8])>(F);
+
+impl<F: FnMut(&[u8])> Write for CallbackWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        (self.0)(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
 /// Read bytes from the decode_buffer that are no longer needed. While the frame is not yet finished
 /// this will retain window_size bytes, else it will drain it completely
 impl Read for FrameDecoder {
@@ -1,6 +1,8 @@
 use crate::decoding::bit_reader::BitReader;
 use crate::decoding::bit_reader_reverse::{BitReaderReversed, GetBitsError};
+use alloc::string::String;
 use alloc::vec::Vec;
+use core::fmt::Write;
 
 /// FSE decoding involves a decoding table that describes the probabilities of
 /// all literals from 0 to the highest present one
@@ -170,7 +172,7 @@ fn highest_bit_set(x: u32) -> u32 {
 
 impl<'t> FSEDecoder<'t> {
     /// Initialize a new Finite State Entropy decoder.
-    pub fn new(table: &'t FSETable) -> FSEDecoder<'_> {
+    pub fn new(table: &'t FSETable) -> FSEDecoder<'t> {
         FSEDecoder {
             state: table.decode.first().copied().unwrap_or(Entry {
                 base_line: 0,
@@ -242,6 +244,36 @@ impl FSETable {
         self.accuracy_log = 0;
     }
 
+    /// A human readable listing of the table: the probability of each symbol followed by all states.
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        if self.decode.is_empty() {
+            out.push_str("Empty FSE table\n");
+            return out;
+        }
+        let _ = writeln!(
+            out,
+            "FSE table with accuracy_log {} ({} states)",
+            self.accuracy_log,
+            self.decode.len()
+        );
+        let _ = writeln!(out, "symbol  probability");
+        for (symbol, prob) in self.symbol_probabilities.iter().enumerate() {
+            if *prob != 0 {
+                let _ = writeln!(out, "{:<6}  {}", symbol, prob);
+            }
+        }
+        let _ = writeln!(out, "state  symbol  base_line  num_bits");
+        for (state, entry) in self.decode.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "{:<5}  {:<6}  {:<9}  {}",
+                state, entry.symbol, entry.base_line, entry.num_bits
+            );
+        }
+        out
+    }
+
     /// returns how many BYTEs (not bits) were read while building the decoder
     pub fn build_decoder(&mut self, source: &[u8], max_log: u8) -> Result<usize, FSETableError> {
         self.accuracy_log = 0;
@@ -261,7 +293,8 @@ impl FSETable {
         if acc_log == 0 {
             return Err(FSETableError::AccLogIsZero);
         }
-        self.symbol_probabilities = probs.to_vec();
+        self.symbol_probabilities.clear();
+        self.symbol_probabilities.extend_from_slice(probs);
         self.accuracy_log = acc_log;
         self.build_decoding_table();
         Ok(())
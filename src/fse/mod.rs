@@ -14,3 +14,5 @@
 
 mod fse_decoder;
 pub use fse_decoder::*;
+mod normalize;
+pub use normalize::*;
@@ -0,0 +1,139 @@
+//! Normalizing symbol counts into the probability distribution an FSE table is built from.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// How [normalize_counts_with] rounds the scaled counts to whole slots of the table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NormalizeStrategy {
+    /// Round all probabilities down and raise the probability of the most probable symbol by what is left over
+    RaiseMax,
+    /// Round all probabilities down and give the slots that are left over to the symbols that lost the most
+    /// by rounding down
+    #[default]
+    ProportionalRemainder,
+    /// Round like the reference implementation's fast path: small probabilities are rounded up from a threshold
+    /// that depends on the probability, what is left over goes to the most probable symbol
+    FastReference,
+}
+
+/// Scale `counts` to a probability distribution whose probabilities sum up to `1 << acc_log`, as used by
+/// [FSETable::build_from_probabilities](crate::fse::FSETable::build_from_probabilities).
+///
+/// Symbols that occur but are too rare to get a full slot of the table are marked with the "less than one"
+/// probability `-1`, which still takes up one slot. Symbols that don't occur get a probability of 0. Rounding
+/// errors are handled as described by [NormalizeStrategy::default].
+///
+/// If all counts are zero, all probabilities are zero.
+///
+/// # Panics
+///
+/// If more symbols occur than the table has slots, or `acc_log` is bigger than 30.
+///
+/// ```
+/// let probs = ruzstd::fse::normalize_counts(&[6, 1, 0, 1], 2);
+/// assert_eq!(probs, [2, -1, 0, -1]);
+/// ```
+pub fn normalize_counts(counts: &[usize], acc_log: u8) -> Vec<i32> {
+    normalize_counts_with(counts, acc_log, NormalizeStrategy::default())
+}
+
+/// Like [normalize_counts] but with the provided strategy to round the probabilities
+pub fn normalize_counts_with(
+    counts: &[usize],
+    acc_log: u8,
+    strategy: NormalizeStrategy,
+) -> Vec<i32> {
+    assert!(acc_log <= 30, "Accuracy log {} is too big", acc_log);
+    let table_size = 1u64 << acc_log;
+    let used_symbols = counts.iter().filter(|&&count| count > 0).count() as u64;
+    assert!(
+        used_symbols <= table_size,
+        "{} symbols don't fit into a table with {} slots",
+        used_symbols,
+        table_size
+    );
+
+    let mut probs = vec![0i32; counts.len()];
+    let total: u64 = counts.iter().map(|&count| count as u64).sum();
+    if total == 0 {
+        return probs;
+    }
+
+    match strategy {
+        NormalizeStrategy::RaiseMax | NormalizeStrategy::ProportionalRemainder => {
+            for (prob, &count) in probs.iter_mut().zip(counts) {
+                if count == 0 {
+                    continue;
+                }
+                let scaled = count as u64 * table_size / total;
+                *prob = if scaled == 0 { -1 } else { scaled as i32 };
+            }
+        }
+        NormalizeStrategy::FastReference => {
+            // the thresholds of the fractional part (in 1 / 2^20) at which a probability below 8 is rounded up
+            const ROUND_UP_THRESHOLDS: [u64; 8] =
+                [0, 473195, 504333, 520860, 550000, 700000, 750000, 830000];
+            let scale = 62 - u32::from(acc_log);
+            let step = (1u64 << 62) / total;
+            let fraction_step = 1u64 << (scale - 20);
+            let low_threshold = total >> acc_log;
+            for (prob, &count) in probs.iter_mut().zip(counts) {
+                let count = count as u64;
+                if count == 0 {
+                    continue;
+                }
+                if count <= low_threshold {
+                    *prob = -1;
+                    continue;
+                }
+                let scaled = count * step;
+                let mut rounded = scaled >> scale;
+                if rounded < 8 {
+                    let rest_to_beat = fraction_step * ROUND_UP_THRESHOLDS[rounded as usize];
+                    if scaled - (rounded << scale) > rest_to_beat {
+                        rounded += 1;
+                    }
+                }
+                *prob = rounded as i32;
+            }
+        }
+    }
+    let mut remaining =
+        table_size as i64 - probs.iter().map(|prob| i64::from(prob.abs())).sum::<i64>();
+
+    if strategy == NormalizeStrategy::ProportionalRemainder && remaining > 0 {
+        // the part of each scaled count that was lost by rounding down, symbols marked with -1 were rounded up
+        let mut losses: Vec<(u64, usize)> = probs
+            .iter()
+            .zip(counts)
+            .enumerate()
+            .filter(|&(_, (&prob, _))| prob > 0)
+            .map(|(idx, (_, &count))| (count as u64 * table_size % total, idx))
+            .collect();
+        losses.sort_unstable_by(|a, b| b.cmp(a));
+        for &(_, idx) in losses.iter().take(remaining as usize) {
+            probs[idx] += 1;
+            remaining -= 1;
+        }
+    }
+
+    // rounding down leaves slots over, rounding up (e.g. the rarest symbols to -1) may take too many
+    while remaining != 0 {
+        let (idx, _) = probs
+            .iter()
+            .enumerate()
+            .filter(|&(_, &prob)| remaining > 0 || prob > 1)
+            .max_by_key(|&(_, &prob)| prob)
+            .expect("Enough slots for all symbols were asserted above");
+        if remaining > 0 {
+            probs[idx] += remaining as i32;
+            remaining = 0;
+        } else {
+            probs[idx] -= 1;
+            remaining += 1;
+        }
+    }
+
+    probs
+}
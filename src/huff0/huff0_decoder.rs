@@ -2,7 +2,9 @@
 
 use crate::decoding::bit_reader_reverse::{BitReaderReversed, GetBitsError};
 use crate::fse::{FSEDecoder, FSEDecoderError, FSETable, FSETableError};
+use alloc::string::String;
 use alloc::vec::Vec;
+use core::fmt::Write;
 #[cfg(feature = "std")]
 use std::error::Error as StdError;
 
@@ -63,6 +65,22 @@ pub enum HuffmanTableError {
     MaxBitsTooHigh {
         got: u8,
     },
+    TooManyCodes {
+        got: usize,
+    },
+    InvalidCode {
+        symbol: u8,
+        code: u32,
+        num_bits: u8,
+    },
+    CodesNotPrefixFree {
+        symbol: u8,
+        other: u8,
+    },
+    CodesIncomplete {
+        used: u32,
+        available: u32,
+    },
 }
 
 #[cfg(feature = "std")]
@@ -149,6 +167,34 @@ impl core::fmt::Display for HuffmanTableError {
                     got, MAX_MAX_NUM_BITS,
                 )
             }
+            HuffmanTableError::TooManyCodes { got } => {
+                write!(f, "Got {} codes but there are only 256 symbols", got)
+            }
+            HuffmanTableError::InvalidCode {
+                symbol,
+                code,
+                num_bits,
+            } => {
+                write!(
+                    f,
+                    "Code {:#b} of symbol {} does not fit into {} bits or is longer than {} bits",
+                    code, symbol, num_bits, MAX_MAX_NUM_BITS,
+                )
+            }
+            HuffmanTableError::CodesNotPrefixFree { symbol, other } => {
+                write!(
+                    f,
+                    "Codes of the symbols {} and {} are a prefix of one another",
+                    other, symbol,
+                )
+            }
+            HuffmanTableError::CodesIncomplete { used, available } => {
+                write!(
+                    f,
+                    "Codes only use {} of the {} entries of the table, some bit sequences can't be decoded",
+                    used, available,
+                )
+            }
         }
     }
 }
@@ -220,6 +266,9 @@ pub struct Entry {
 /// The Zstandard specification limits the maximum length of a code to 11 bits.
 const MAX_MAX_NUM_BITS: u8 = 11;
 
+/// The Zstandard specification limits the accuracy log of the FSE table used to compress the weights to 6.
+const MAX_WEIGHTS_ACC_LOG: u8 = 6;
+
 /// Assert that the provided value is greater than zero, and returns the
 /// 32 - the number of leading zeros
 fn highest_bit_set(x: u32) -> u32 {
@@ -315,6 +364,17 @@ impl HuffmanTable {
         self.fse_table.reinit_from(&other.fse_table);
     }
 
+    /// Allocate everything the biggest possible table needs, so building tables afterwards does not allocate.
+    pub fn preallocate(&mut self) {
+        let max_ranks = MAX_MAX_NUM_BITS as usize + 1;
+        self.decode.reserve(1 << MAX_MAX_NUM_BITS);
+        self.weights.reserve(256);
+        self.bits.reserve(257);
+        self.bit_ranks.reserve(max_ranks);
+        self.rank_indexes.reserve(max_ranks);
+        self.fse_table.decode.reserve(1 << MAX_WEIGHTS_ACC_LOG);
+    }
+
     /// Completely empty the table of all data.
     pub fn reset(&mut self) {
         self.decode.clear();
@@ -326,6 +386,34 @@ impl HuffmanTable {
         self.fse_table.reset();
     }
 
+    /// A human readable listing of the table, with the weight, code length and code of each symbol in it.
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        if self.decode.is_empty() {
+            out.push_str("Empty huffman table\n");
+            return out;
+        }
+        let _ = writeln!(out, "Huffman table with max_num_bits {}", self.max_num_bits);
+        let _ = writeln!(out, "symbol  weight  num_bits  code");
+        // each symbol takes up a consecutive range of entries, starting with its code followed by zeros
+        let mut idx = 0;
+        while idx < self.decode.len() {
+            let entry = self.decode[idx];
+            let unused_bits = self.max_num_bits - entry.num_bits;
+            let _ = writeln!(
+                out,
+                "0x{:02x}    {:<6}  {:<8}  {:0width$b}",
+                entry.symbol,
+                self.max_num_bits + 1 - entry.num_bits,
+                entry.num_bits,
+                idx >> unused_bits,
+                width = entry.num_bits as usize,
+            );
+            idx += 1 << unused_bits;
+        }
+        out
+    }
+
     /// Read from `source` and parse it into a huffman table.
     ///
     /// Returns the number of bytes read.
@@ -337,6 +425,116 @@ impl HuffmanTable {
         Ok(bytes_used)
     }
 
+    /// Build the table from the weights of the symbols, like they are stored in the table description.
+    ///
+    /// `weights[n]` is the weight of symbol `n`, a weight of 0 means the symbol does not occur. The weight of the
+    /// last symbol is not part of `weights`, it is implied by the others. So at most 255 weights can be passed.
+    /// Returns an error instead of building the table if the weights don't describe a valid distribution.
+    pub fn build_from_weights(&mut self, weights: &[u8]) -> Result<(), HuffmanTableError> {
+        if weights.len() > 255 {
+            return Err(HuffmanTableError::TooManyWeights { got: weights.len() });
+        }
+        self.decode.clear();
+        self.weights.clear();
+        self.weights.extend_from_slice(weights);
+        let result = self.build_table_from_weights();
+        if result.is_err() {
+            self.reset();
+        }
+        result
+    }
+
+    /// Build a table from the code of each symbol, e.g. to decode with a known table.
+    ///
+    /// `codes[n]` is the code of symbol `n` and its length in bits, a length of 0 means the symbol does not occur.
+    /// Unlike the tables built from weights, the codes don't have to be canonical. They have to be prefix free, at most
+    /// 11 bits long and complete, so that every sequence of bits can be decoded.
+    ///
+    /// ```
+    /// use ruzstd::huff0::HuffmanTable;
+    ///
+    /// let table = HuffmanTable::from_codes(vec![(0b0, 1), (0b10, 2), (0b11, 2)]).unwrap();
+    /// assert_eq!(table.max_num_bits, 2);
+    /// assert!(HuffmanTable::from_codes(vec![(0b0, 1), (0b01, 2)]).is_err());
+    /// ```
+    pub fn from_codes(codes: Vec<(u32, u8)>) -> Result<HuffmanTable, HuffmanTableError> {
+        use HuffmanTableError as err;
+
+        if codes.len() > 256 {
+            return Err(err::TooManyCodes { got: codes.len() });
+        }
+        for (symbol, &(code, num_bits)) in codes.iter().enumerate() {
+            if num_bits > MAX_MAX_NUM_BITS || code >> num_bits != 0 {
+                return Err(err::InvalidCode {
+                    symbol: symbol as u8,
+                    code,
+                    num_bits,
+                });
+            }
+        }
+
+        let max_bits = codes
+            .iter()
+            .map(|&(_, num_bits)| num_bits)
+            .max()
+            .unwrap_or(0);
+        let mut table = HuffmanTable::new();
+        table.max_num_bits = max_bits;
+        table.decode.resize(
+            1 << max_bits,
+            Entry {
+                symbol: 0,
+                num_bits: 0,
+            },
+        );
+
+        let mut used = 0;
+        for (symbol, &(code, num_bits)) in codes.iter().enumerate() {
+            if num_bits == 0 {
+                continue;
+            }
+            // a code ignores the max_bits - num_bits bits after it, so it spans all entries starting with it
+            let len = 1 << (max_bits - num_bits);
+            let base_idx = (code as usize) << (max_bits - num_bits);
+            for entry in &mut table.decode[base_idx..base_idx + len] {
+                if entry.num_bits != 0 {
+                    return Err(err::CodesNotPrefixFree {
+                        symbol: symbol as u8,
+                        other: entry.symbol,
+                    });
+                }
+                entry.symbol = symbol as u8;
+                entry.num_bits = num_bits;
+            }
+            used += len as u32;
+        }
+        if used == 0 || used as usize != table.decode.len() {
+            return Err(err::CodesIncomplete {
+                used,
+                available: table.decode.len() as u32,
+            });
+        }
+
+        // keep the code lengths and weights like a table built from weights does, the last weight is implied
+        let last_symbol = codes
+            .iter()
+            .rposition(|&(_, num_bits)| num_bits > 0)
+            .expect("At least one code is used");
+        table
+            .bits
+            .extend(codes[..=last_symbol].iter().map(|&(_, num_bits)| num_bits));
+        table
+            .weights
+            .extend(table.bits[..last_symbol].iter().map(|&num_bits| {
+                if num_bits > 0 {
+                    max_bits + 1 - num_bits
+                } else {
+                    0
+                }
+            }));
+        Ok(table)
+    }
+
     /// Read weights from the provided source.
     ///
     /// The huffman table is represented in the encoded data as a list of weights
@@ -368,7 +566,7 @@ impl HuffmanTable {
                 //fse decompress weights
                 let bytes_used_by_fse_header = self
                     .fse_table
-                    .build_decoder(fse_stream, /*TODO find actual max*/ 100)?;
+                    .build_decoder(fse_stream, MAX_WEIGHTS_ACC_LOG)?;
 
                 if bytes_used_by_fse_header > header as usize {
                     return Err(err::FSETableUsedTooManyBytes {
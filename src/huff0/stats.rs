@@ -0,0 +1,38 @@
+//! Statistics over the symbols of some data, the first step of building entropy coding tables.
+
+/// Count how often each byte value occurs in `data`. The count of a byte is at the index of its value.
+///
+/// ```
+/// let counts = ruzstd::huff0::symbol_counts(b"abracadabra");
+/// assert_eq!(counts[b'a' as usize], 5);
+/// assert_eq!(counts[b'z' as usize], 0);
+/// ```
+pub fn symbol_counts(data: &[u8]) -> [usize; 256] {
+    let mut counts = [0usize; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+    counts
+}
+
+/// The order-0 Shannon entropy of `data` in bits per byte, based on [symbol_counts].
+///
+/// This is the lower bound of what any entropy coding that looks at each byte on its own, like huffman coding,
+/// can achieve. Empty data has an entropy of 0.
+///
+/// ```
+/// assert_eq!(ruzstd::huff0::block_entropy(b"aaaa"), 0.0);
+/// assert_eq!(ruzstd::huff0::block_entropy(b"abab"), 1.0);
+/// ```
+#[cfg(feature = "std")]
+pub fn block_entropy(data: &[u8]) -> f64 {
+    let total = data.len() as f64;
+    symbol_counts(data)
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let probability = count as f64 / total;
+            -probability * probability.log2()
+        })
+        .sum()
+}
@@ -24,6 +24,7 @@ pub mod frame;
 pub mod frame_decoder;
 pub mod fse;
 pub mod huff0;
+pub mod push_decoder;
 pub mod streaming_decoder;
 mod tests;
 
@@ -36,6 +37,15 @@ pub mod io_nostd;
 #[cfg(not(feature = "std"))]
 pub use io_nostd as io;
 
+pub use frame::{
+    frames, is_zstd_magic, requires_dictionary, MAGIC_NUM, SKIPPABLE_MAGIC_NUM_MAX,
+    SKIPPABLE_MAGIC_NUM_MIN,
+};
+pub use frame_decoder::decode_one_frame;
+pub use frame_decoder::validate_frame;
 pub use frame_decoder::BlockDecodingStrategy;
 pub use frame_decoder::FrameDecoder;
+pub use frame_decoder::FrameDecoderOptions;
+pub use push_decoder::PushDecoder;
+pub use streaming_decoder::decode_from_reader;
 pub use streaming_decoder::StreamingDecoder;
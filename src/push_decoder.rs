@@ -0,0 +1,193 @@
+use alloc::vec::Vec;
+
+use crate::frame::ReadFrameHeaderError;
+use crate::frame_decoder::{FrameDecoder, FrameDecoderError};
+use crate::io::ErrorKind;
+#[cfg(feature = "std")]
+use std::error::Error as StdError;
+
+/// Zstandard frame decoder that is fed the compressed data in chunks of any size, instead of reading it from a source.
+///
+/// This suits non-blocking sources: whatever bytes are available are passed to [PushDecoder::decode_chunk],
+/// which decodes as much as it can and signals when it needs more input to make progress.
+/// Bytes that don't complete a block yet are kept by the decoder, so the input never has to be passed twice.
+/// Skippable frames are consumed without producing any output.
+///
+/// ```
+/// use ruzstd::push_decoder::{ChunkStatus, PushDecoder};
+///
+/// fn decode_frame(chunks: &[&[u8]]) -> Vec<u8> {
+///     let mut decoder = PushDecoder::new();
+///     let mut result = Vec::new();
+///     for chunk in chunks {
+///         let (_consumed, status) = decoder.decode_chunk(chunk).unwrap();
+///         match status {
+///             ChunkStatus::Output(output) => result.extend(output),
+///             ChunkStatus::NeedMoreInput => { /* wait for the next chunk */ }
+///             ChunkStatus::FrameFinished(output) => {
+///                 result.extend(output);
+///                 break;
+///             }
+///         }
+///     }
+///     result
+/// }
+/// ```
+pub struct PushDecoder {
+    pub decoder: FrameDecoder,
+    /// Input of the current frame that could not be decoded yet
+    pending: Vec<u8>,
+    /// Whether the header of the current frame has been read
+    in_frame: bool,
+    /// How many bytes of a skippable frame still have to be skipped
+    skip_left: usize,
+}
+
+/// What a call to [PushDecoder::decode_chunk] produced
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChunkStatus {
+    /// Decoded content of the frame. All input was consumed, pass more to continue.
+    Output(Vec<u8>),
+    /// All input was consumed without producing output, more input is needed to make progress.
+    NeedMoreInput,
+    /// The frame has been decoded completely, this is the rest of its content.
+    /// Input after the end of the frame is not consumed, the next call starts decoding a new frame with it.
+    FrameFinished(Vec<u8>),
+}
+
+/// Returned by [PushDecoder::decode_chunk] if the data could not be decoded.
+/// The decoder drops the frame that failed to decode, the next call starts decoding a new frame.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct PushDecoderError {
+    /// How many bytes of the input were consumed before the part that failed to decode
+    pub consumed: usize,
+    pub source: FrameDecoderError,
+}
+
+#[cfg(feature = "std")]
+impl StdError for PushDecoderError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl core::fmt::Display for PushDecoderError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        write!(
+            f,
+            "Failed to decode the input after consuming {} bytes of it: {}",
+            self.consumed, self.source,
+        )
+    }
+}
+
+impl Default for PushDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PushDecoder {
+    pub fn new() -> PushDecoder {
+        Self::with_decoder(FrameDecoder::new())
+    }
+
+    /// Like [PushDecoder::new] but decodes with the provided decoder, e.g. one with dictionaries added to it
+    pub fn with_decoder(decoder: FrameDecoder) -> PushDecoder {
+        PushDecoder {
+            decoder,
+            pending: Vec::new(),
+            in_frame: false,
+            skip_left: 0,
+        }
+    }
+
+    /// Decode as much of the frame as possible with `input` and the bytes of previous calls that could not be decoded yet.
+    ///
+    /// Returns how many bytes of `input` were consumed and what was produced. All of `input` is consumed,
+    /// unless the frame ended within it.
+    pub fn decode_chunk(&mut self, input: &[u8]) -> Result<(usize, ChunkStatus), PushDecoderError> {
+        self.pending.extend_from_slice(input);
+
+        match self.decode_pending() {
+            Ok(status @ ChunkStatus::FrameFinished(_)) => {
+                // everything that is left belongs to whatever follows the frame
+                let consumed = input.len() - self.pending.len();
+                self.pending.clear();
+                Ok((consumed, status))
+            }
+            Ok(status) => Ok((input.len(), status)),
+            Err(source) => {
+                // the part that failed to decode is still at the start of the pending bytes
+                let consumed = input.len().saturating_sub(self.pending.len());
+                self.pending.clear();
+                self.in_frame = false;
+                self.skip_left = 0;
+                Err(PushDecoderError { consumed, source })
+            }
+        }
+    }
+
+    fn decode_pending(&mut self) -> Result<ChunkStatus, FrameDecoderError> {
+        while !self.in_frame {
+            if self.skip_left > 0 {
+                let skipped = usize::min(self.skip_left, self.pending.len());
+                self.pending.drain(..skipped);
+                self.skip_left -= skipped;
+                if self.skip_left > 0 {
+                    return Ok(ChunkStatus::NeedMoreInput);
+                }
+            }
+
+            let mut source = self.pending.as_slice();
+            let result = self.decoder.reset(&mut source);
+            let header_len = self.pending.len() - source.len();
+            match result {
+                Ok(()) => self.in_frame = true,
+                Err(FrameDecoderError::ReadFrameHeaderError(ReadFrameHeaderError::SkipFrame {
+                    length,
+                    ..
+                })) => self.skip_left = length as usize,
+                Err(FrameDecoderError::ReadFrameHeaderError(err)) if is_incomplete(&err) => {
+                    return Ok(ChunkStatus::NeedMoreInput);
+                }
+                Err(err) => return Err(err),
+            }
+            self.pending.drain(..header_len);
+        }
+
+        while !self.decoder.is_finished() {
+            let (read, _) = self.decoder.decode_from_to(&self.pending, &mut [])?;
+            if read == 0 {
+                break;
+            }
+            self.pending.drain(..read);
+        }
+
+        if self.decoder.is_finished() {
+            self.in_frame = false;
+            let output = self.decoder.collect().unwrap_or_default();
+            return Ok(ChunkStatus::FrameFinished(output));
+        }
+
+        match self.decoder.collect() {
+            Some(output) if !output.is_empty() => Ok(ChunkStatus::Output(output)),
+            _ => Ok(ChunkStatus::NeedMoreInput),
+        }
+    }
+}
+
+/// Whether reading the frame header failed only because there were not enough bytes yet
+fn is_incomplete(err: &ReadFrameHeaderError) -> bool {
+    match err {
+        ReadFrameHeaderError::MagicNumberReadError(e)
+        | ReadFrameHeaderError::FrameDescriptorReadError(e)
+        | ReadFrameHeaderError::WindowDescriptorReadError(e)
+        | ReadFrameHeaderError::DictionaryIdReadError(e)
+        | ReadFrameHeaderError::FrameContentSizeReadError(e) => {
+            e.kind() == ErrorKind::UnexpectedEof
+        }
+        _ => false,
+    }
+}
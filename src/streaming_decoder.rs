@@ -1,5 +1,6 @@
 use core::borrow::BorrowMut;
 
+use crate::frame::{self, ReadFrameHeaderError};
 use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder, FrameDecoderError};
 use crate::io::{Error, ErrorKind, Read};
 
@@ -14,13 +15,12 @@ use crate::io::{Error, ErrorKind, Read};
 /// [FrameDecoder::decode_blocks] repeatedly to decode the entire frame.
 ///
 /// ## Caveat
-/// [StreamingDecoder] expects the underlying stream to only contain a single frame,
+/// [StreamingDecoder] only reads a single frame at a time and reports the end of data after it,
 /// yet the specification states that a single archive may contain multiple frames.
 ///
-/// To decode all the frames in a finite stream, the calling code needs to recreate
-/// the instance of the decoder and handle
-/// [crate::frame::ReadFrameHeaderError::SkipFrame]
-/// errors by skipping forward the `length` amount of bytes, see <https://github.com/KillingSpark/zstd-rs/issues/57>
+/// To decode all the frames in a finite stream, use [StreamingDecoder::next_frame] after
+/// a frame has been read completely. It skips over skippable frames and starts decoding the next frame.
+/// [StreamingDecoder::has_next_frame] tells if there is another frame without starting to decode it.
 ///
 /// ```no_run
 /// // `read_to_end` is not implemented by the no_std implementation.
@@ -40,6 +40,12 @@ use crate::io::{Error, ErrorKind, Read};
 pub struct StreamingDecoder<READ: Read, DEC: BorrowMut<FrameDecoder>> {
     pub decoder: DEC,
     source: READ,
+    peeked: [u8; 4],
+    peeked_len: usize,
+    /// See [StreamingDecoder::set_max_output_size]
+    max_output_size: Option<u64>,
+    /// How many bytes have been read from the decoder over all frames
+    output_size: u64,
 }
 
 impl<READ: Read, DEC: BorrowMut<FrameDecoder>> StreamingDecoder<READ, DEC> {
@@ -48,17 +54,149 @@ impl<READ: Read, DEC: BorrowMut<FrameDecoder>> StreamingDecoder<READ, DEC> {
         mut decoder: DEC,
     ) -> Result<StreamingDecoder<READ, DEC>, FrameDecoderError> {
         decoder.borrow_mut().init(&mut source)?;
-        Ok(StreamingDecoder { decoder, source })
+        Ok(Self::from_initialized(source, decoder))
+    }
+
+    /// Wraps a decoder that has already read the header of the first frame from `source`
+    fn from_initialized(source: READ, decoder: DEC) -> StreamingDecoder<READ, DEC> {
+        StreamingDecoder {
+            decoder,
+            source,
+            peeked: [0; 4],
+            peeked_len: 0,
+            max_output_size: None,
+            output_size: 0,
+        }
+    }
+
+    /// Limit how many bytes the decoder produces in total over all frames, `None` removes the limit.
+    /// There is no limit by default.
+    ///
+    /// Once the limit is reached, reads that would produce more bytes return an error. Decoding stops there too,
+    /// so a small input that decompresses to huge amounts of data can't make the decoder do more work than that.
+    pub fn set_max_output_size(&mut self, max_output_size: Option<u64>) {
+        self.max_output_size = max_output_size;
+    }
+
+    /// Checks if the source contains another frame (or skippable frame) after the current one.
+    /// This can only be answered after the current frame has been decoded completely, before that an error is returned.
+    ///
+    /// The magic number of the next frame is not consumed. If the bytes after the current frame are not a frame
+    /// they can be retrieved with [StreamingDecoder::peeked_bytes].
+    pub fn has_next_frame(&mut self) -> Result<bool, Error> {
+        if !self.decoder.borrow().is_finished() {
+            return Err(other_error(
+                "The current frame has not been decoded completely",
+            ));
+        }
+
+        while self.peeked_len < self.peeked.len() {
+            match self.source.read(&mut self.peeked[self.peeked_len..]) {
+                Ok(0) => break,
+                Ok(n) => self.peeked_len += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if self.peeked_len < self.peeked.len() {
+            return Ok(false);
+        }
+
+        Ok(frame::is_zstd_magic(&self.peeked))
+    }
+
+    /// The bytes that [StreamingDecoder::has_next_frame] read from the source but were not consumed yet.
+    /// They come before anything that is still left in the source.
+    pub fn peeked_bytes(&self) -> &[u8] {
+        &self.peeked[..self.peeked_len]
+    }
+
+    /// Starts decoding the next frame in the source, skipping over any skippable frames on the way.
+    /// Returns false if there is no further frame, see [StreamingDecoder::has_next_frame].
+    /// Returns [FrameDecoderError::FrameNotFinished] if the current frame has not been decoded completely.
+    ///
+    /// Note that bytes of the current frame that have not been read yet are lost.
+    pub fn next_frame(&mut self) -> Result<bool, FrameDecoderError> {
+        use FrameDecoderError as err;
+        if !self.decoder.borrow().is_finished() {
+            return Err(err::FrameNotFinished);
+        }
+        loop {
+            let has_next = self.has_next_frame().map_err(|e| {
+                err::ReadFrameHeaderError(ReadFrameHeaderError::MagicNumberReadError(e))
+            })?;
+            if !has_next {
+                return Ok(false);
+            }
+
+            let mut source = PeekedSource {
+                peeked: &self.peeked[..self.peeked_len],
+                source: &mut self.source,
+            };
+            let result = self.decoder.borrow_mut().reset(&mut source);
+            // the magic number is always read completely
+            self.peeked_len = 0;
+
+            match result {
+                Ok(()) => return Ok(true),
+                Err(err::ReadFrameHeaderError(ReadFrameHeaderError::SkipFrame {
+                    length, ..
+                })) => skip_frame_content(&mut self.source, length)?,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Reads and discards the `length` bytes of content of a skippable frame whose header has already been read
+fn skip_frame_content(mut source: impl Read, length: u32) -> Result<(), FrameDecoderError> {
+    let mut buf = [0u8; 512];
+    let mut left = length as usize;
+    while left > 0 {
+        let chunk = usize::min(left, buf.len());
+        source
+            .read_exact(&mut buf[..chunk])
+            .map_err(FrameDecoderError::FailedToSkipFrame)?;
+        left -= chunk;
+    }
+    Ok(())
+}
+
+/// Reads the peeked bytes before continuing with the source
+struct PeekedSource<'a, READ: Read> {
+    peeked: &'a [u8],
+    source: &'a mut READ,
+}
+
+impl<READ: Read> Read for PeekedSource<'_, READ> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if self.peeked.is_empty() {
+            self.source.read(buf)
+        } else {
+            self.peeked.read(buf)
+        }
     }
 }
 
+#[cfg(feature = "std")]
+fn other_error(
+    e: impl Into<alloc::boxed::Box<dyn std::error::Error + Send + Sync + 'static>>,
+) -> Error {
+    Error::new(ErrorKind::Other, e)
+}
+
+#[cfg(not(feature = "std"))]
+fn other_error(e: impl core::fmt::Display + Send + Sync + 'static) -> Error {
+    Error::new(ErrorKind::Other, alloc::boxed::Box::new(e))
+}
+
 impl<READ: Read> StreamingDecoder<READ, FrameDecoder> {
     pub fn new(
         mut source: READ,
     ) -> Result<StreamingDecoder<READ, FrameDecoder>, FrameDecoderError> {
         let mut decoder = FrameDecoder::new();
         decoder.init(&mut source)?;
-        Ok(StreamingDecoder { decoder, source })
+        Ok(StreamingDecoder::from_initialized(source, decoder))
     }
 
     pub fn inner(self) -> FrameDecoder {
@@ -66,42 +204,146 @@ impl<READ: Read> StreamingDecoder<READ, FrameDecoder> {
     }
 }
 
-impl<READ: Read, DEC: BorrowMut<FrameDecoder>> Read for StreamingDecoder<READ, DEC> {
-    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+impl<READ: Read, DEC: BorrowMut<FrameDecoder>> StreamingDecoder<READ, DEC> {
+    /// Decode until at least `amount` bytes can be collected or the frame is finished
+    fn decode_until_collectable(&mut self, amount: usize) -> Result<(), Error> {
         let decoder = self.decoder.borrow_mut();
-        if decoder.is_finished() && decoder.can_collect() == 0 {
-            //No more bytes can ever be decoded
-            return Ok(0);
-        }
-
         // need to loop. The UpToBytes strategy doesn't take any effort to actually reach that limit.
         // The first few calls can result in just filling the decode buffer but these bytes can not be collected.
         // So we need to call this until we can actually collect enough bytes
 
         // TODO add BlockDecodingStrategy::UntilCollectable(usize) that pushes this logic into the decode_blocks function
-        while decoder.can_collect() < buf.len() && !decoder.is_finished() {
+        while decoder.can_collect() < amount && !decoder.is_finished() {
             //More bytes can be decoded
-            let additional_bytes_needed = buf.len() - decoder.can_collect();
+            let additional_bytes_needed = amount - decoder.can_collect();
             match decoder.decode_blocks(
                 &mut self.source,
                 BlockDecodingStrategy::UptoBytes(additional_bytes_needed),
             ) {
                 Ok(_) => { /*Nothing to do*/ }
-                Err(e) => {
-                    let err;
-                    #[cfg(feature = "std")]
-                    {
-                        err = Error::new(ErrorKind::Other, e);
-                    }
-                    #[cfg(not(feature = "std"))]
-                    {
-                        err = Error::new(ErrorKind::Other, alloc::boxed::Box::new(e));
-                    }
-                    return Err(err);
-                }
+                Err(e) => return Err(other_error(e)),
+            }
+        }
+        Ok(())
+    }
+
+    /// How many of the `wanted` bytes may be read without going over the limit of [StreamingDecoder::set_max_output_size].
+    /// Errors if the limit has been reached and there are more bytes to read.
+    fn allowed_output(&mut self, wanted: usize) -> Result<usize, Error> {
+        let Some(max_output_size) = self.max_output_size else {
+            return Ok(wanted);
+        };
+        let left = max_output_size.saturating_sub(self.output_size);
+        if left == 0 && wanted > 0 {
+            self.decode_until_collectable(1)?;
+            if self.decoder.borrow().can_collect() > 0 {
+                return Err(other_error(alloc::format!(
+                    "Decoded output exceeds the limit of {} bytes",
+                    max_output_size
+                )));
             }
         }
+        Ok(u64::min(wanted as u64, left) as usize)
+    }
+}
+
+impl<READ: Read, DEC: BorrowMut<FrameDecoder>> Read for StreamingDecoder<READ, DEC> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let decoder = self.decoder.borrow_mut();
+        if decoder.is_finished() && decoder.can_collect() == 0 {
+            //No more bytes can ever be decoded
+            return Ok(0);
+        }
+
+        let len = self.allowed_output(buf.len())?;
+        self.decode_until_collectable(len)?;
+        let read = self.decoder.borrow_mut().read(&mut buf[..len])?;
+        self.output_size += read as u64;
+        Ok(read)
+    }
+
+    /// Decodes enough for all buffers at once and then fills them one after another.
+    /// Only the last buffer that gets any bytes can be filled partially.
+    #[cfg(feature = "std")]
+    fn read_vectored(&mut self, bufs: &mut [std::io::IoSliceMut<'_>]) -> Result<usize, Error> {
+        let decoder = self.decoder.borrow_mut();
+        if decoder.is_finished() && decoder.can_collect() == 0 {
+            //No more bytes can ever be decoded
+            return Ok(0);
+        }
+
+        let total_len = self.allowed_output(bufs.iter().map(|buf| buf.len()).sum())?;
+        self.decode_until_collectable(total_len)?;
+
+        let decoder = self.decoder.borrow_mut();
+        let mut read = 0;
+        for buf in bufs.iter_mut() {
+            let len = usize::min(buf.len(), total_len - read);
+            let n = decoder.read(&mut buf[..len])?;
+            read += n;
+            if n < buf.len() {
+                break;
+            }
+        }
+        self.output_size += read as u64;
+        Ok(read)
+    }
+}
+
+/// Frame content sizes bigger than this are not trusted for preallocating the output in [decode_from_reader]
+const MAX_CONTENT_SIZE_PREALLOCATION: u64 = 1024 * 1024 * 16;
+
+/// Decodes all frames from the source into a single vec.
+///
+/// The output is preallocated with the `size_hint` if one is given, else with the content size of the first frame
+/// if its header declares one. Skippable frames are skipped, any other data after the last frame is an error.
+pub fn decode_from_reader<READ: Read>(
+    source: READ,
+    size_hint: Option<usize>,
+) -> Result<alloc::vec::Vec<u8>, Error> {
+    let mut source = source;
+    let mut frame_decoder = FrameDecoder::new();
+    // StreamingDecoder::new errors on a skippable frame, so skip leading ones before starting the first frame
+    loop {
+        match frame_decoder.init(&mut source) {
+            Ok(()) => break,
+            Err(FrameDecoderError::ReadFrameHeaderError(ReadFrameHeaderError::SkipFrame {
+                length,
+                ..
+            })) => skip_frame_content(&mut source, length).map_err(other_error)?,
+            Err(e) => return Err(other_error(e)),
+        }
+    }
+    let mut decoder = StreamingDecoder::from_initialized(source, frame_decoder);
+
+    let capacity = size_hint.unwrap_or_else(|| {
+        let content_size = decoder.decoder.current_frame_content_size().unwrap_or(0);
+        u64::min(content_size, MAX_CONTENT_SIZE_PREALLOCATION) as usize
+    });
+    let mut result = alloc::vec::Vec::with_capacity(capacity);
+
+    loop {
+        // checking the decoder instead of reading until 0 is returned avoids growing an exactly sized vec
+        while !decoder.decoder.is_finished() || decoder.decoder.can_collect() > 0 {
+            let len = result.len();
+            let additional = match result.capacity() - len {
+                0 => 32 * 1024,
+                spare => spare,
+            };
+            result.resize(len + additional, 0);
+            let read = decoder.read(&mut result[len..])?;
+            result.truncate(len + read);
+        }
+
+        if !decoder.next_frame().map_err(other_error)? {
+            break;
+        }
+    }
 
-        decoder.read(buf)
+    if !decoder.peeked_bytes().is_empty() {
+        return Err(other_error(
+            "Found data after the last frame that is not a frame",
+        ));
     }
+    Ok(result)
 }
@@ -0,0 +1,93 @@
+extern crate std;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Counts the allocations of the current thread while counting is enabled, so other tests running at the same time
+/// don't interfere
+struct CountingAllocator;
+
+std::thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    let _ = COUNTING.try_with(|counting| {
+        if counting.get() {
+            ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        }
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns the result of `f` and how many allocations it made
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    ALLOCATIONS.with(|allocations| allocations.set(0));
+    COUNTING.with(|counting| counting.set(true));
+    let result = f();
+    COUNTING.with(|counting| counting.set(false));
+    (result, ALLOCATIONS.with(|allocations| allocations.get()))
+}
+
+#[test]
+fn test_decode_with_scratch_does_not_allocate() {
+    use crate::decoding::scratch::DecoderScratch;
+    use crate::frame_decoder::FrameDecoder;
+    use alloc::vec;
+    use std::fs;
+
+    // z000022 has the biggest window of these, 2.5MB
+    let files = ["z000001", "z000022", "z000088", "z000090"];
+    let mut frame_dec = FrameDecoder::with_scratch(DecoderScratch::with_capacity(2621440));
+    for file in files {
+        let content = fs::read(std::format!("./decodecorpus_files/{}.zst", file)).unwrap();
+        let original = fs::read(std::format!("./decodecorpus_files/{}", file)).unwrap();
+        let mut target = vec![0u8; original.len()];
+
+        let (written, allocations) = count_allocations(|| {
+            let mut source = content.as_slice();
+            frame_dec.reset(&mut source).unwrap();
+            let mut written = 0;
+            while !frame_dec.is_finished() || frame_dec.can_collect() > 0 {
+                let (read, wrote) = frame_dec
+                    .decode_from_to(source, &mut target[written..])
+                    .unwrap();
+                source = &source[read..];
+                written += wrote;
+            }
+            written
+        });
+        assert_eq!(allocations, 0, "decoding {} allocated", file);
+        assert_eq!(written, original.len());
+        assert_eq!(target, original);
+    }
+
+    // without the scratch the first frame allocates it
+    let content = fs::read("./decodecorpus_files/z000088.zst").unwrap();
+    let mut frame_dec = FrameDecoder::new();
+    let (_, allocations) = count_allocations(|| frame_dec.reset(content.as_slice()).unwrap());
+    assert!(allocations > 0);
+}
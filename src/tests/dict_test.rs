@@ -74,6 +74,111 @@ fn test_dict_parsing() {
     }
 }
 
+#[cfg(feature = "hash")]
+#[test]
+fn test_dict_derive_id() {
+    use crate::decoding::dictionary::Dictionary;
+
+    let content = b"some dictionary content, some dictionary content";
+    let id = Dictionary::derive_id(content);
+    assert_eq!(id, Dictionary::derive_id(content));
+    assert_ne!(id, Dictionary::derive_id(&content[1..]));
+    assert_ne!(id, Dictionary::derive_id(b""));
+
+    for content in [&b""[..], b"a", content] {
+        let id = Dictionary::derive_id(content);
+        assert!((32768..1 << 31).contains(&id));
+    }
+}
+
+#[test]
+fn test_requires_dictionary() {
+    use crate::decoding::dictionary::Dictionary;
+    use crate::frame::requires_dictionary;
+    extern crate std;
+    use std::fs;
+
+    let dict = fs::read("./dict_tests/dictionary").unwrap();
+    let dict = Dictionary::decode_dict(&dict).unwrap();
+    let dict_frame = fs::read("./dict_tests/files/ModemManager.service.zst").unwrap();
+    assert_eq!(requires_dictionary(&dict_frame).unwrap(), Some(dict.id));
+
+    let plain_frame = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    assert_eq!(requires_dictionary(&plain_frame).unwrap(), None);
+
+    // dictionary id 0 in the header means no dictionary is needed
+    let zero_id = [0x28, 0xb5, 0x2f, 0xfd, 0x01, 0x50, 0x00];
+    assert_eq!(requires_dictionary(&zero_id).unwrap(), None);
+
+    assert!(requires_dictionary(&[0x28, 0xb5]).is_err());
+    assert!(requires_dictionary(&[0, 0, 0, 0, 0, 0]).is_err());
+}
+
+#[test]
+fn test_four_byte_dict_id() {
+    use crate::decoding::dictionary::Dictionary;
+    use crate::frame::read_frame_header;
+    extern crate std;
+    use std::fs;
+
+    // the id of the dictionary needs all four bytes, so zstd uses the largest dictionary id field
+    let dict = fs::read("./dict_tests/dictionary").unwrap();
+    let dict = Dictionary::decode_dict(&dict).unwrap();
+    assert!(dict.id > 0xFFFF);
+    let dict_frame = fs::read("./dict_tests/files/ModemManager.service.zst").unwrap();
+    let (frame, _) = read_frame_header(&dict_frame[..]).unwrap();
+    assert_eq!(frame.header.descriptor.dict_id_flag(), 3);
+    assert_eq!(frame.header.descriptor.dictionary_id_bytes().unwrap(), 4);
+    assert_eq!(frame.header.dictionary_id(), Some(dict.id));
+
+    // single segment with a four byte dictionary id that uses the highest bit, one content size byte
+    // and a raw block with "abc"
+    let frame = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x23, 0xfe, 0xff, 0xff, 0xff, 0x03, 0x19, 0x00, 0x00, b'a', b'b',
+        b'c',
+    ];
+    let (header, bytes_read) = read_frame_header(&frame[..]).unwrap();
+    assert_eq!(bytes_read, 10);
+    assert_eq!(header.header.dictionary_id(), Some(0xFFFF_FFFE));
+    assert_eq!(header.header.frame_content_size(), 3);
+}
+
+#[test]
+fn test_dict_matches_beyond_window() {
+    use crate::decoding::dictionary::Dictionary;
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    extern crate std;
+    use std::fs;
+
+    let raw_dict = fs::read("./dict_tests/dictionary").unwrap();
+    let mut original = raw_dict[2000..2600].to_vec();
+    original.extend_from_slice(b"some new bytes that are not in the dictionary");
+    original.extend_from_slice(&raw_dict[5000..5300]);
+
+    // `zstd -19 --no-check -D ./dict_tests/dictionary` of the original. This is a single segment frame,
+    // so the window is only as big as the content, but the first block matches far back into the dictionary.
+    let frame = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x63, 0x48, 0x70, 0xde, 0x24, 0xb1, 0x02, 0x6d, 0x01, 0x00, 0x80,
+        0x74, 0x6e, 0x65, 0x77, 0x20, 0x62, 0x79, 0x74, 0x65, 0x73, 0x64, 0x69, 0x63, 0x74, 0x69,
+        0x6f, 0x07, 0xf0, 0x29, 0xfa, 0xb9, 0x15, 0x80, 0x6c, 0xe0, 0xeb, 0x19, 0x9f, 0xe2, 0x07,
+        0xbd, 0x2a, 0x4e, 0x11, 0xec, 0xa9, 0x0b, 0xa5, 0xd2, 0x32, 0xac, 0x16, 0x4b, 0x05,
+    ];
+
+    let mut frame_dec = FrameDecoder::new();
+    frame_dec
+        .add_dict(Dictionary::decode_dict(&raw_dict).unwrap())
+        .unwrap();
+    let (header, _) = crate::frame::read_frame_header(&frame[..]).unwrap();
+    assert_eq!(header.header.window_size().unwrap(), original.len() as u64);
+    let mut source = &frame[..];
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert!(source.is_empty());
+    assert_eq!(frame_dec.collect().unwrap(), original);
+}
+
 #[test]
 fn test_dict_decoding() {
     extern crate std;
@@ -130,6 +235,7 @@ fn test_dict_decoding() {
             .unwrap();
         let result = frame_dec.collect().unwrap();
         let end_time = start_time.elapsed();
+        assert_eq!(frame_dec.positions(), (file_size, result.len() as u64));
 
         match frame_dec.get_checksum_from_data() {
             Some(chksum) => {
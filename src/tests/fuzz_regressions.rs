@@ -24,3 +24,52 @@ fn test_all_artifacts() {
         });
     }
 }
+
+#[test]
+fn test_acc_log_out_of_range() {
+    use crate::blocks::block::BlockType;
+    use crate::decoding::block_decoder::{DecodeBlockContentError, DecompressBlockError};
+    use crate::decoding::literals_section_decoder::DecompressLiteralsError;
+    use crate::decoding::sequence_section_decoder::DecodeSequenceError;
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder, FrameDecoderError};
+    use crate::fse::FSETableError;
+    use crate::huff0::HuffmanTableError;
+
+    fn decode(block: &[u8]) -> FrameDecoderError {
+        let frame = super::single_segment_frame(&[(BlockType::Compressed, block)]);
+        let mut frame_dec = FrameDecoder::new();
+        let mut source = frame.as_slice();
+        frame_dec.reset(&mut source).unwrap();
+        frame_dec
+            .decode_blocks(&mut source, BlockDecodingStrategy::All)
+            .unwrap_err()
+    }
+
+    // no literals, one sequence with an FSE compressed literal lengths table declaring an accuracy log of 10
+    let err = decode(&[0x00, 0x01, 0x80, 0x05, 0xff, 0xff, 0xff, 0xff]);
+    assert!(matches!(
+        err,
+        FrameDecoderError::FailedToReadBlockBody(DecodeBlockContentError::DecompressBlockError {
+            source: DecompressBlockError::DecodeSequenceError(DecodeSequenceError::FSETableError(
+                FSETableError::AccLogTooBig { got: 10, max: 9 }
+            )),
+            ..
+        })
+    ));
+
+    // four huffman coded literals, the weights are FSE compressed with an accuracy log of 7
+    let err = decode(&[
+        0x42, 0x00, 0x02, 0x02, 0x02, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00,
+    ]);
+    assert!(matches!(
+        err,
+        FrameDecoderError::FailedToReadBlockBody(DecodeBlockContentError::DecompressBlockError {
+            source: DecompressBlockError::DecompressLiteralsError(
+                DecompressLiteralsError::HuffmanTableError(HuffmanTableError::FSETableError(
+                    FSETableError::AccLogTooBig { got: 7, max: 6 }
+                )),
+            ),
+            ..
+        })
+    ));
+}
@@ -1,88 +1,2275 @@
+// the tests read the small reference files byte by byte, buffering them is not worth the noise
+#![allow(clippy::unbuffered_bytes)]
+
 #[cfg(test)]
 use alloc::vec;
 
-#[cfg(test)]
-use alloc::vec::Vec;
+#[cfg(test)]
+use alloc::vec::Vec;
+
+#[cfg(test)]
+extern crate std;
+
+#[cfg(all(test, not(feature = "std")))]
+impl crate::io_nostd::Read for std::fs::File {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, crate::io_nostd::Error> {
+        std::io::Read::read(self, buf).map_err(|e| {
+            if e.get_ref().is_none() {
+                crate::io_nostd::Error::from(crate::io_nostd::ErrorKind::Other)
+            } else {
+                crate::io_nostd::Error::new(
+                    crate::io_nostd::ErrorKind::Other,
+                    alloc::boxed::Box::new(e.into_inner().unwrap()),
+                )
+            }
+        })
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+#[allow(dead_code)]
+fn assure_error_impl() {
+    // not a real test just there to throw an compiler error if Error is not derived correctly
+
+    use crate::frame_decoder::FrameDecoderError;
+    let _err: &dyn std::error::Error = &FrameDecoderError::NotYetInitialized;
+}
+
+#[cfg(all(test, feature = "std"))]
+#[allow(dead_code)]
+fn assure_decoder_send_sync() {
+    // not a real test just there to throw an compiler error if FrameDecoder is Send + Sync
+
+    use crate::frame_decoder::FrameDecoder;
+    let decoder = FrameDecoder::new();
+    std::thread::spawn(move || {
+        drop(decoder);
+    });
+}
+
+/// Builds a single segment frame without content size and checksum that holds the raw or compressed `blocks`,
+/// the last one is marked as the last block.
+#[cfg(test)]
+fn single_segment_frame(blocks: &[(crate::blocks::block::BlockType, &[u8])]) -> Vec<u8> {
+    use crate::blocks::block::BlockType;
+
+    let mut frame = vec![0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x50];
+    for (idx, (block_type, block)) in blocks.iter().enumerate() {
+        let type_bits = match block_type {
+            BlockType::Raw => 0,
+            BlockType::Compressed => 2,
+            other => panic!("Block_Size is not the content length for {:?}", other),
+        };
+        let last = (idx == blocks.len() - 1) as u32;
+        let header = ((block.len() as u32) << 3) | (type_bits << 1) | last;
+        frame.extend_from_slice(&header.to_le_bytes()[..3]);
+        frame.extend_from_slice(block);
+    }
+    frame
+}
+
+#[test]
+fn skippable_frame() {
+    use crate::frame;
+
+    let mut content = vec![];
+    content.extend_from_slice(&0x184D2A50u32.to_le_bytes());
+    content.extend_from_slice(&300u32.to_le_bytes());
+    assert_eq!(8, content.len());
+    let err = frame::read_frame_header(content.as_slice());
+    assert!(matches!(
+        err,
+        Err(frame::ReadFrameHeaderError::SkipFrame {
+            magic_number: 0x184D2A50u32,
+            length: 300
+        })
+    ));
+
+    content.clear();
+    content.extend_from_slice(&0x184D2A5Fu32.to_le_bytes());
+    content.extend_from_slice(&0xFFFFFFFFu32.to_le_bytes());
+    assert_eq!(8, content.len());
+    let err = frame::read_frame_header(content.as_slice());
+    assert!(matches!(
+        err,
+        Err(frame::ReadFrameHeaderError::SkipFrame {
+            magic_number: 0x184D2A5Fu32,
+            length: 0xFFFFFFFF
+        })
+    ));
+}
+
+#[test]
+fn test_magic_numbers() {
+    use crate::{is_zstd_magic, MAGIC_NUM, SKIPPABLE_MAGIC_NUM_MAX, SKIPPABLE_MAGIC_NUM_MIN};
+
+    assert_eq!(MAGIC_NUM, 0xFD2FB528);
+    assert_eq!(SKIPPABLE_MAGIC_NUM_MIN, 0x184D2A50);
+    assert_eq!(SKIPPABLE_MAGIC_NUM_MAX, 0x184D2A5F);
+
+    assert!(is_zstd_magic(&[0x28, 0xB5, 0x2F, 0xFD]));
+    assert!(is_zstd_magic(&[0x28, 0xB5, 0x2F, 0xFD, 0x24, 0x00]));
+    assert!(is_zstd_magic(&[0x50, 0x2A, 0x4D, 0x18]));
+    assert!(is_zstd_magic(&[0x5F, 0x2A, 0x4D, 0x18, 0x00]));
+    assert!(is_zstd_magic(
+        &std::fs::read("./decodecorpus_files/z000001.zst").unwrap()
+    ));
+
+    assert!(!is_zstd_magic(&[]));
+    assert!(!is_zstd_magic(&[0x28, 0xB5, 0x2F]));
+    assert!(!is_zstd_magic(&[0xFD, 0x2F, 0xB5, 0x28]));
+    assert!(!is_zstd_magic(&[0x60, 0x2A, 0x4D, 0x18]));
+    assert!(!is_zstd_magic(b"PK\x03\x04"));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_encode_skippable_frame() {
+    use crate::frame::{self, encode_skippable_frame, SkippableFrameError};
+    use crate::streaming_decoder::StreamingDecoder;
+    use std::io::Read;
+
+    assert!(matches!(
+        encode_skippable_frame(16, b""),
+        Err(SkippableFrameError::InvalidMagicVariant { got: 16 })
+    ));
+
+    let skippable = encode_skippable_frame(15, b"some metadata").unwrap();
+    assert!(crate::is_zstd_magic(&skippable));
+    let mut source = skippable.as_slice();
+    let err = frame::read_frame_header(&mut source);
+    assert!(matches!(
+        err,
+        Err(frame::ReadFrameHeaderError::SkipFrame {
+            magic_number: 0x184D2A5F,
+            length: 13
+        })
+    ));
+    assert_eq!(source, b"some metadata");
+
+    let empty = encode_skippable_frame(0, b"").unwrap();
+    assert_eq!(empty, [0x50, 0x2A, 0x4D, 0x18, 0, 0, 0, 0]);
+
+    // the decoder skips over them
+    let mut content = std::fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    content.extend_from_slice(&skippable);
+    content.extend_from_slice(&empty);
+    content.extend_from_slice(&std::fs::read("./decodecorpus_files/z000090.zst").unwrap());
+    let mut stream = StreamingDecoder::new(content.as_slice()).unwrap();
+    stream.read_to_end(&mut Vec::new()).unwrap();
+    assert!(stream.next_frame().unwrap());
+    let mut result = Vec::new();
+    stream.read_to_end(&mut result).unwrap();
+    assert_eq!(
+        result,
+        std::fs::read("./decodecorpus_files/z000090").unwrap()
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_frame_header_reading() {
+    use crate::frame;
+    use std::fs;
+
+    let mut content = fs::File::open("./decodecorpus_files/z000088.zst").unwrap();
+    let (_frame, _) = frame::read_frame_header(&mut content).unwrap();
+}
+
+#[test]
+fn test_frame_content_size_encodings() {
+    use crate::frame;
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+
+    // single segment frames with a single RLE block of `size` bytes
+    fn rle_frame(descriptor: u8, fcs: &[u8], size: u32) -> Vec<u8> {
+        let mut content = vec![];
+        content.extend_from_slice(&0xFD2FB528u32.to_le_bytes());
+        content.push(descriptor);
+        content.extend_from_slice(fcs);
+        content.extend_from_slice(&((size << 3) | (1 << 1) | 1).to_le_bytes()[..3]);
+        content.push(0xAB);
+        content
+    }
+
+    let frames = [
+        // 1 byte field
+        (rle_frame(0x20, &[255], 255), 255),
+        // 2 byte field stores the size - 256
+        (rle_frame(0x60, &[0, 0], 256), 256),
+        (rle_frame(0x60, &[1, 0], 257), 257),
+        (rle_frame(0x60, &[0xFF, 0xFF], 65791), 65791),
+        // 4 byte field has no offset
+        (rle_frame(0xA0, &65792u32.to_le_bytes(), 65792), 65792),
+    ];
+
+    let mut frame_dec = FrameDecoder::new();
+    for (content, size) in frames {
+        let (frame, header_size) = frame::read_frame_header(content.as_slice()).unwrap();
+        assert_eq!(frame.header.frame_content_size(), size);
+        assert_eq!(header_size as usize, content.len() - 4);
+
+        let mut source = content.as_slice();
+        frame_dec.reset(&mut source).unwrap();
+        frame_dec
+            .decode_blocks(&mut source, BlockDecodingStrategy::All)
+            .unwrap();
+        let result = frame_dec.collect().unwrap();
+        assert_eq!(result.len() as u64, size);
+        assert!(result.iter().all(|&b| b == 0xAB));
+    }
+}
+
+#[test]
+fn test_checksum_without_content_size() {
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use core::convert::TryInto;
+    use std::fs;
+
+    // z000001.zst has a checksum but no frame content size
+    let mut content = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    let frame_len = content.len();
+    let original = fs::read("./decodecorpus_files/z000001").unwrap();
+    let expected_checksum = u32::from_le_bytes(content[frame_len - 4..].try_into().unwrap());
+    content.extend_from_slice(b"trailing data");
+
+    let mut frame_dec = FrameDecoder::new();
+    let mut source = content.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    assert!(frame_dec.current_frame_content_size().is_none());
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert!(frame_dec.is_finished());
+    assert_eq!(frame_dec.get_checksum_from_data(), Some(expected_checksum));
+    assert_eq!(frame_dec.bytes_read_from_source(), frame_len as u64);
+    assert_eq!(source, b"trailing data");
+    assert_eq!(frame_dec.collect().unwrap(), original);
+    #[cfg(feature = "hash")]
+    assert_eq!(frame_dec.get_calculated_checksum(), Some(expected_checksum));
+
+    // same for the slice based api
+    let mut frame_dec = FrameDecoder::new();
+    let mut target = vec![0u8; original.len()];
+    let (read, written) = frame_dec
+        .decode_from_to(&content[..frame_len], &mut target)
+        .unwrap();
+    assert_eq!(read, frame_len);
+    assert_eq!(written, original.len());
+    assert_eq!(target, original);
+    assert_eq!(frame_dec.get_checksum_from_data(), Some(expected_checksum));
+}
+
+#[test]
+fn test_ignore_checksum() {
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder, FrameDecoderOptions};
+    use std::fs;
+
+    // z000001.zst has a checksum, overwrite it with garbage
+    let mut content = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    let frame_len = content.len();
+    content[frame_len - 4..].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+    let original = fs::read("./decodecorpus_files/z000001").unwrap();
+
+    let mut frame_dec =
+        FrameDecoder::with_options(FrameDecoderOptions::new().ignore_checksum(true));
+    let mut source = content.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert!(frame_dec.is_finished());
+    assert!(source.is_empty());
+    assert_eq!(frame_dec.collect().unwrap(), original);
+    assert_eq!(
+        frame_dec.get_checksum_from_data(),
+        Some(u32::from_le_bytes([0xde, 0xad, 0xbe, 0xef]))
+    );
+    #[cfg(feature = "hash")]
+    assert_eq!(frame_dec.get_calculated_checksum(), None);
+
+    // with the checksum calculated the mismatch is visible
+    frame_dec.set_ignore_checksum(false);
+    let mut source = content.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert_eq!(frame_dec.collect().unwrap(), original);
+    #[cfg(feature = "hash")]
+    assert_ne!(
+        frame_dec.get_calculated_checksum(),
+        frame_dec.get_checksum_from_data()
+    );
+
+    // toggling the option in the middle of a frame only affects the next frame
+    let content = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    for ignore_first in [false, true] {
+        frame_dec.set_ignore_checksum(ignore_first);
+        let mut source = content.as_slice();
+        frame_dec.reset(&mut source).unwrap();
+        let mut result = Vec::new();
+        let mut toggle = !ignore_first;
+        while !frame_dec.is_finished() {
+            frame_dec
+                .decode_blocks(&mut source, BlockDecodingStrategy::UptoBlocks(1))
+                .unwrap();
+            result.extend(frame_dec.collect().unwrap_or_default());
+            frame_dec.set_ignore_checksum(toggle);
+            toggle = !toggle;
+        }
+        assert!(frame_dec.blocks_decoded() > 2);
+        assert_eq!(result, original);
+        #[cfg(feature = "hash")]
+        if ignore_first {
+            assert_eq!(frame_dec.get_calculated_checksum(), None);
+        } else {
+            assert_eq!(
+                frame_dec.get_calculated_checksum(),
+                frame_dec.get_checksum_from_data()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_empty_last_block() {
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+
+    // `printf '' | zstd -c`
+    let empty_frame = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x24, 0x00, 0x01, 0x00, 0x00, 0x99, 0xe9, 0xd8, 0x51,
+    ];
+    // a raw block with content followed by an empty raw block that ends the frame
+    let mut frame = vec![0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x48];
+    frame.extend_from_slice(&(5u32 << 3).to_le_bytes()[..3]);
+    frame.extend_from_slice(b"hello");
+    frame.extend_from_slice(&[0x01, 0x00, 0x00]);
+
+    let mut frame_dec = FrameDecoder::new();
+    for (content, expected) in [(&empty_frame[..], &b""[..]), (&frame, b"hello")] {
+        let mut source = content;
+        frame_dec.reset(&mut source).unwrap();
+        assert!(frame_dec
+            .decode_blocks(&mut source, BlockDecodingStrategy::All)
+            .unwrap());
+        assert!(frame_dec.is_finished());
+        assert!(source.is_empty());
+        assert_eq!(frame_dec.collect().unwrap(), expected);
+
+        let mut frame_dec = FrameDecoder::new();
+        let mut target = [0u8; 16];
+        let (read, written) = frame_dec.decode_from_to(content, &mut target).unwrap();
+        assert_eq!(read, content.len());
+        assert_eq!(&target[..written], expected);
+        assert!(frame_dec.is_finished());
+    }
+}
+
+#[test]
+fn test_huff0_stream_boundaries() {
+    use crate::blocks::literals_section::LiteralsSection;
+    use crate::frame::read_frame_header;
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use crate::huff0::HuffmanTable;
+
+    // same generator as used to create the frames below with `zstd -1 --no-check`
+    fn generate(len: usize) -> Vec<u8> {
+        let table = b"aaaaaabbbbbccccdddeefghijk";
+        let mut x: u32 = 1;
+        (0..len)
+            .map(|_| {
+                x = x.wrapping_mul(1103515245).wrapping_add(12345) & 0x7fff_ffff;
+                table[(x >> 16) as usize % table.len()]
+            })
+            .collect()
+    }
+
+    fn decode(frame: &[u8]) -> Result<Vec<u8>, crate::frame_decoder::FrameDecoderError> {
+        let mut frame_dec = FrameDecoder::new();
+        let mut source = frame;
+        frame_dec.reset(&mut source)?;
+        frame_dec.decode_blocks(&mut source, BlockDecodingStrategy::All)?;
+        Ok(frame_dec.collect().unwrap_or_default())
+    }
+
+    fn is_missing_sentinel(err: crate::frame_decoder::FrameDecoderError) -> bool {
+        use crate::decoding::block_decoder::{DecodeBlockContentError, DecompressBlockError};
+        use crate::decoding::literals_section_decoder::DecompressLiteralsError;
+        matches!(
+            err,
+            crate::frame_decoder::FrameDecoderError::FailedToReadBlockBody(
+                DecodeBlockContentError::DecompressBlockError {
+                    source: DecompressBlockError::DecompressLiteralsError(
+                        DecompressLiteralsError::ExtraPadding { .. }
+                    ),
+                    ..
+                }
+            )
+        )
+    }
+
+    // literals with a single huffman stream
+    let single = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x48, 0xfd, 0x02, 0x00, 0x82, 0xcc, 0x16, 0x0c, 0xd0, 0xa5,
+        0x03, 0x84, 0x90, 0x4d, 0xf6, 0x96, 0x0c, 0x00, 0xe0, 0x22, 0xdb, 0xab, 0x45, 0x68, 0x6e,
+        0xfd, 0x64, 0x14, 0xf7, 0x94, 0xdd, 0x18, 0xff, 0x74, 0x43, 0x8a, 0x19, 0x34, 0x6b, 0x27,
+        0x13, 0x22, 0x91, 0x08, 0x3b, 0xd4, 0xfc, 0x72, 0xd8, 0xcc, 0xd0, 0xb3, 0x8c, 0x61, 0x5c,
+        0x05, 0x19, 0xe0, 0x9d, 0x1b, 0x1d, 0x5d, 0xa3, 0xf8, 0x45, 0xe9, 0xe0, 0xc6, 0xc5, 0xff,
+        0x1c, 0xf8, 0x10, 0x81, 0x0a, 0x52, 0x8c, 0xd7, 0x2c, 0x74, 0x1b, 0xb5, 0xea, 0x6e, 0xd2,
+        0x0d, 0x23, 0x0e, 0xe5, 0x39, 0x4d, 0xcd, 0xc2, 0x8e, 0xdb, 0x2d, 0x35, 0x09, 0x00,
+    ];
+    // literals split into four huffman streams, each stream ends at a different bit position
+    let four_256 = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x48, 0xed, 0x03, 0x00, 0x06, 0x50, 0x1e, 0x0c, 0xd0, 0xa5,
+        0x03, 0x84, 0x90, 0x4d, 0xf6, 0x96, 0x0c, 0x00, 0xe0, 0x22, 0x19, 0x00, 0x19, 0x00, 0x1a,
+        0x00, 0x40, 0x05, 0x29, 0xc6, 0x6b, 0x16, 0xba, 0x8d, 0x5a, 0x75, 0x37, 0xe9, 0x86, 0x11,
+        0x87, 0xf2, 0x9c, 0xa6, 0x66, 0x61, 0xc7, 0xed, 0x96, 0x9a, 0x04, 0x9b, 0x19, 0x7a, 0x96,
+        0x31, 0x8c, 0xab, 0x20, 0x03, 0xbc, 0x73, 0xa3, 0xa3, 0x6b, 0x14, 0xbf, 0x28, 0x1d, 0xdc,
+        0xb8, 0xf8, 0x9f, 0x03, 0x1f, 0x62, 0x34, 0xb7, 0x7e, 0x32, 0x8a, 0x7b, 0xca, 0x6e, 0x8c,
+        0x7f, 0xba, 0x21, 0xc5, 0x0c, 0x9a, 0xb5, 0x93, 0x09, 0x91, 0x48, 0x84, 0x1d, 0x6a, 0x7e,
+        0x39, 0x04, 0x3f, 0x99, 0xd9, 0x28, 0xe2, 0x3f, 0xd2, 0xf0, 0x01, 0xc1, 0x8a, 0x04, 0x1c,
+        0x9e, 0x66, 0x48, 0x04, 0x3e, 0x59, 0x5a, 0x65, 0xd3, 0xed, 0xd5, 0x22, 0x01, 0x00,
+    ];
+    let four_259 = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x48, 0xfd, 0x03, 0x00, 0x36, 0xd0, 0x1e, 0x0d, 0xc0, 0x25,
+        0x1d, 0xa4, 0x91, 0xdd, 0xe4, 0x96, 0x0c, 0x00, 0x00, 0xe7, 0x05, 0x19, 0x00, 0x1a, 0x00,
+        0x1a, 0x00, 0x02, 0x1a, 0x8c, 0xc1, 0x7c, 0xec, 0x40, 0x25, 0x31, 0x56, 0x5d, 0x3d, 0xba,
+        0x61, 0xa2, 0x50, 0x96, 0xcf, 0xc6, 0x14, 0x76, 0x6a, 0xb5, 0x0c, 0x49, 0x07, 0x6d, 0x9b,
+        0xb7, 0xcc, 0x30, 0x35, 0x82, 0x08, 0xe0, 0x95, 0x9a, 0x6a, 0x1e, 0x33, 0xf8, 0x45, 0xe7,
+        0xa0, 0xe6, 0xc1, 0xbf, 0x1c, 0x70, 0x21, 0x01, 0x8b, 0x8c, 0xdc, 0xf1, 0x92, 0x29, 0x6a,
+        0x29, 0x2b, 0x21, 0x7e, 0xa9, 0x36, 0x06, 0x13, 0x23, 0xc7, 0x4a, 0x14, 0x88, 0x33, 0x41,
+        0xd0, 0x61, 0xec, 0x97, 0x13, 0xe1, 0x25, 0x9b, 0xa6, 0x88, 0xff, 0x1c, 0xe1, 0x02, 0x04,
+        0x34, 0x46, 0x80, 0xc3, 0xcf, 0x36, 0x22, 0xe0, 0x92, 0x9d, 0x55, 0x24, 0x95, 0xd6, 0x08,
+        0x00,
+    ];
+
+    assert_eq!(decode(&single).unwrap(), generate(200));
+    assert_eq!(decode(&four_256).unwrap(), generate(256));
+    assert_eq!(decode(&four_259).unwrap(), generate(259));
+
+    for frame in [&four_256[..], &four_259[..]] {
+        // find the jump table behind the frame header, block header, literals header and huffman table
+        let (_, header_len) = read_frame_header(frame).unwrap();
+        let literals = &frame[header_len as usize + 3..];
+        let mut section = LiteralsSection::new();
+        let literals_header_len = section.parse_from_header(literals).unwrap() as usize;
+        assert_eq!(section.num_streams, Some(4));
+        let mut table = HuffmanTable::new();
+        let table_len = table
+            .build_decoder(&literals[literals_header_len..])
+            .unwrap() as usize;
+        let jump_table = header_len as usize + 3 + literals_header_len + table_len;
+
+        // move the boundary between two neighbouring streams, which misaligns both of them
+        for stream in 0..3 {
+            for shift in [-1i32, 1] {
+                let mut corrupted = frame.to_vec();
+                for (idx, delta) in [(stream, shift), (stream + 1, -shift)] {
+                    if idx == 3 {
+                        // the size of the last stream is implied
+                        continue;
+                    }
+                    let pos = jump_table + 2 * idx;
+                    let size = u16::from_le_bytes([corrupted[pos], corrupted[pos + 1]]);
+                    let size = (size as i32 + delta) as u16;
+                    corrupted[pos..pos + 2].copy_from_slice(&size.to_le_bytes());
+                }
+                assert!(
+                    decode(&corrupted).is_err(),
+                    "stream {} shifted by {} was not detected",
+                    stream + 1,
+                    shift
+                );
+            }
+        }
+
+        // clear the last byte of each stream, which holds the sentinel bit
+        let mut stream_end = jump_table + 6;
+        for idx in 0..4 {
+            stream_end = if idx == 3 {
+                header_len as usize
+                    + 3
+                    + literals_header_len
+                    + section.compressed_size.unwrap() as usize
+            } else {
+                let pos = jump_table + 2 * idx;
+                stream_end + u16::from_le_bytes([frame[pos], frame[pos + 1]]) as usize
+            };
+            let mut corrupted = frame.to_vec();
+            corrupted[stream_end - 1] = 0;
+            assert!(
+                is_missing_sentinel(decode(&corrupted).unwrap_err()),
+                "missing sentinel in stream {} was not detected",
+                idx + 1
+            );
+        }
+    }
+
+    // same for the single stream
+    let (_, header_len) = read_frame_header(&single[..]).unwrap();
+    let mut section = LiteralsSection::new();
+    let literals_header_len = section
+        .parse_from_header(&single[header_len as usize + 3..])
+        .unwrap() as usize;
+    assert_eq!(section.num_streams, Some(1));
+    let stream_end =
+        header_len as usize + 3 + literals_header_len + section.compressed_size.unwrap() as usize;
+    let mut corrupted = single.to_vec();
+    corrupted[stream_end - 1] = 0;
+    assert!(is_missing_sentinel(decode(&corrupted).unwrap_err()));
+}
+
+#[test]
+fn test_huff0_unequal_last_stream() {
+    use crate::blocks::literals_section::LiteralsSection;
+    use crate::frame::read_frame_header;
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use crate::huff0::HuffmanTable;
+
+    // the first three quarters only use 4 symbols, the last one 16, so the last stream is the longest.
+    // Compressed with `zstd -1 --no-check --zstd=mml=7`, which leaves all 295 bytes as literals
+    let mut x: u32 = 2;
+    let original: Vec<u8> = (0..295)
+        .map(|idx| {
+            x = x.wrapping_mul(1103515245).wrapping_add(12345);
+            let table: &[u8] = if idx < 295 * 3 / 4 {
+                b"abcd"
+            } else {
+                b"abcdefghijklmnop"
+            };
+            table[(x >> 16) as usize % table.len()]
+        })
+        .collect();
+    let frame = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x60, 0x27, 0x00, 0x7d, 0x04, 0x00, 0x76, 0xd2, 0x22, 0x11, 0xc0,
+        0x25, 0xcb, 0x01, 0x84, 0xb4, 0x4d, 0x76, 0xdb, 0x7b, 0x93, 0x24, 0x19, 0x00, 0x00, 0xce,
+        0x0b, 0x15, 0x00, 0x15, 0x00, 0x16, 0x00, 0x67, 0x6d, 0x39, 0xfa, 0x7d, 0x97, 0xdc, 0xbc,
+        0xb4, 0xe4, 0xbb, 0x6c, 0x36, 0x59, 0xec, 0xe4, 0xbe, 0x3d, 0xb6, 0xd9, 0x02, 0x69, 0x7d,
+        0x25, 0xeb, 0xa7, 0xf7, 0xcf, 0x36, 0xa5, 0x2f, 0x97, 0x18, 0xfb, 0xe4, 0xb7, 0xeb, 0x49,
+        0xbe, 0x9d, 0xe9, 0x01, 0x82, 0x94, 0x89, 0xd1, 0x4a, 0x53, 0x65, 0xd4, 0x8b, 0x9a, 0xbe,
+        0x97, 0xcd, 0xf4, 0xc4, 0x7f, 0x63, 0x26, 0xa6, 0xa8, 0xb9, 0x03, 0x6f, 0x78, 0x44, 0x11,
+        0x30, 0x14, 0x07, 0x61, 0x10, 0x28, 0x0a, 0x04, 0x3f, 0x0a, 0x43, 0x21, 0x28, 0x04, 0x11,
+        0x03, 0x40, 0xc0, 0x48, 0x03, 0x40, 0x61, 0x10, 0x37, 0x28, 0x14, 0x83, 0x22, 0x38, 0x88,
+        0x63, 0x20, 0x62, 0x10, 0x02, 0x0a, 0x84, 0x41, 0x0a, 0xc4, 0x51, 0x0c, 0x87, 0xc0, 0x38,
+        0x50, 0x04, 0x00,
+    ];
+
+    // check the layout: 295 literals in four streams, the last one is a lot longer than the others
+    let (_, header_len) = read_frame_header(&frame[..]).unwrap();
+    let literals = &frame[header_len as usize + 3..];
+    let mut section = LiteralsSection::new();
+    let literals_header_len = section.parse_from_header(literals).unwrap() as usize;
+    assert_eq!(section.num_streams, Some(4));
+    assert_eq!(section.regenerated_size, 295);
+    let mut table = HuffmanTable::new();
+    let table_len = table
+        .build_decoder(&literals[literals_header_len..])
+        .unwrap() as usize;
+    let jump_table = &literals[literals_header_len + table_len..][..6];
+    let sizes: Vec<usize> = jump_table
+        .chunks(2)
+        .map(|size| u16::from_le_bytes([size[0], size[1]]) as usize)
+        .collect();
+    let last_size =
+        section.compressed_size.unwrap() as usize - table_len - 6 - sizes.iter().sum::<usize>();
+    assert!(sizes.iter().all(|&size| size * 2 < last_size));
+
+    let mut frame_dec = FrameDecoder::new();
+    let mut source = &frame[..];
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert_eq!(frame_dec.collect().unwrap(), original);
+}
+
+#[test]
+fn test_describe_tables() {
+    use crate::fse::{normalize_counts, FSETable};
+    use crate::huff0::HuffmanTable;
+
+    let mut fse = FSETable::new();
+    assert_eq!(fse.describe(), "Empty FSE table\n");
+    let counts = [10, 0, 3, 0, 0, 1, 20];
+    fse.build_from_probabilities(5, &normalize_counts(&counts, 5))
+        .unwrap();
+    let description = fse.describe();
+    assert!(description.starts_with("FSE table with accuracy_log 5 (32 states)\n"));
+    let (probabilities, states) = description.split_once("\nstate ").unwrap();
+    let listed: Vec<usize> = probabilities
+        .lines()
+        .skip(2)
+        .map(|line| line.split_whitespace().next().unwrap().parse().unwrap())
+        .collect();
+    assert_eq!(listed, [0, 2, 5, 6]);
+    assert_eq!(states.lines().count(), 1 + 32);
+
+    let mut huff = HuffmanTable::new();
+    assert_eq!(huff.describe(), "Empty huffman table\n");
+    // weights given directly: 2, 1 and 1 for the symbols 0 to 2, the weight 3 of symbol 3 is implied
+    huff.build_decoder(&[127 + 3, 0x21, 0x10]).unwrap();
+    let description = huff.describe();
+    assert!(description.starts_with("Huffman table with max_num_bits 3\n"));
+    let symbols: Vec<(&str, &str, &str)> = description
+        .lines()
+        .skip(2)
+        .map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            (columns[0], columns[1], columns[3])
+        })
+        .collect();
+    assert_eq!(symbols.len(), 4);
+    for symbol in ["0x00", "0x01", "0x02", "0x03"] {
+        assert_eq!(symbols.iter().filter(|(s, _, _)| *s == symbol).count(), 1);
+    }
+    // the weights determine the code lengths
+    for (symbol, weight, code) in symbols {
+        let expected_weight = match symbol {
+            "0x00" => "2",
+            "0x03" => "3",
+            _ => "1",
+        };
+        assert_eq!(weight, expected_weight);
+        assert_eq!(code.len(), 4 - weight.parse::<usize>().unwrap());
+    }
+}
+
+#[test]
+fn test_symbol_counts() {
+    use crate::huff0::symbol_counts;
+    use std::fs;
+
+    assert_eq!(symbol_counts(&[]), [0; 256]);
+
+    let data = fs::read("./decodecorpus_files/z000088").unwrap();
+    let counts = symbol_counts(&data);
+    for value in 0..=255u8 {
+        let manual = data.iter().filter(|&&byte| byte == value).count();
+        assert_eq!(counts[value as usize], manual);
+    }
+    assert_eq!(counts.iter().sum::<usize>(), data.len());
+}
+
+#[test]
+fn test_normalize_counts() {
+    use crate::fse::{normalize_counts, FSETable};
+    use crate::huff0::symbol_counts;
+    use std::fs;
+
+    fn check(counts: &[usize], acc_log: u8) {
+        let probs = normalize_counts(counts, acc_log);
+        assert_eq!(probs.len(), counts.len());
+        // a -1 takes up one slot of the table
+        let slots: i32 = probs.iter().map(|prob| prob.abs()).sum();
+        assert_eq!(slots, 1 << acc_log, "counts: {:?}", counts);
+        for (&prob, &count) in probs.iter().zip(counts) {
+            assert_eq!(prob == 0, count == 0);
+        }
+        let mut table = FSETable::new();
+        table.build_from_probabilities(acc_log, &probs).unwrap();
+        assert_eq!(table.decode.len(), 1 << acc_log);
+    }
+
+    assert_eq!(normalize_counts(&[0, 0, 0], 5), [0, 0, 0]);
+    assert_eq!(normalize_counts(&[0, 7, 0], 5), [0, 32, 0]);
+    assert_eq!(normalize_counts(&[1, 1, 1, 1], 2), [1, 1, 1, 1]);
+    check(&[1000, 1, 1, 1], 5);
+    check(&[1, 1, 1, 1, 1, 1, 1, 1, 1000000], 4);
+    check(&[3, 3, 3], 6);
+
+    for file in ["z000001", "z000033", "z000088"] {
+        let data = fs::read(std::format!("./decodecorpus_files/{}", file)).unwrap();
+        let counts = symbol_counts(&data);
+        for acc_log in [8, 9, 11] {
+            check(&counts, acc_log);
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_normalize_strategies() {
+    use crate::fse::{normalize_counts_with, FSETable, NormalizeStrategy};
+    use crate::huff0::symbol_counts;
+    use std::fs;
+
+    const STRATEGIES: [NormalizeStrategy; 3] = [
+        NormalizeStrategy::RaiseMax,
+        NormalizeStrategy::ProportionalRemainder,
+        NormalizeStrategy::FastReference,
+    ];
+
+    // the size in bits the symbols would take up when encoded with the distribution
+    fn encoded_bits(counts: &[usize], probs: &[i32], acc_log: u8) -> f64 {
+        counts
+            .iter()
+            .zip(probs)
+            .filter(|&(&count, _)| count > 0)
+            .map(|(&count, &prob)| {
+                count as f64 * (f64::from(acc_log) - f64::from(prob.abs()).log2())
+            })
+            .sum()
+    }
+
+    let mut totals = [0f64; 3];
+    for file in ["z000001", "z000033", "z000088"] {
+        let data = fs::read(std::format!("./decodecorpus_files/{}", file)).unwrap();
+        let counts = symbol_counts(&data);
+        for acc_log in [8, 9, 11] {
+            for (total, strategy) in totals.iter_mut().zip(STRATEGIES) {
+                let probs = normalize_counts_with(&counts, acc_log, strategy);
+                let slots: i32 = probs.iter().map(|prob| prob.abs()).sum();
+                assert_eq!(slots, 1 << acc_log, "{:?}", strategy);
+                for (&prob, &count) in probs.iter().zip(&counts) {
+                    assert_eq!(prob == 0, count == 0);
+                }
+                let mut table = FSETable::new();
+                table.build_from_probabilities(acc_log, &probs).unwrap();
+
+                let bits = encoded_bits(&counts, &probs, acc_log);
+                std::println!(
+                    "{} acc_log {}: {:?} {} bytes",
+                    file,
+                    acc_log,
+                    strategy,
+                    (bits / 8.0).ceil()
+                );
+                *total += bits;
+            }
+        }
+    }
+
+    let best = totals.iter().cloned().fold(f64::INFINITY, f64::min);
+    let default_idx = STRATEGIES
+        .iter()
+        .position(|&strategy| strategy == NormalizeStrategy::default())
+        .unwrap();
+    assert_eq!(totals[default_idx], best);
+}
+
+#[test]
+fn test_effective_window_size() {
+    use crate::frame::read_frame_header;
+    use crate::frame_decoder::FrameDecoder;
+    use std::fs;
+
+    let mut frame_dec = FrameDecoder::new();
+    assert_eq!(frame_dec.effective_window_size(), 0);
+
+    // single segment frame with a content size of 3 and a raw block with "abc"
+    let frame = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x20, 0x03, 0x19, 0x00, 0x00, b'a', b'b', b'c',
+    ];
+    let (header, _) = read_frame_header(&frame[..]).unwrap();
+    frame_dec.reset(&frame[..]).unwrap();
+    assert_eq!(frame_dec.effective_window_size(), 3);
+    assert_eq!(header.header.window_size().unwrap(), 3);
+
+    // a window descriptor without a content size
+    let frame = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    let (header, _) = read_frame_header(frame.as_slice()).unwrap();
+    frame_dec.reset(frame.as_slice()).unwrap();
+    assert!(!header.header.descriptor.single_segment_flag());
+    assert_eq!(
+        frame_dec.effective_window_size(),
+        header.header.window_size().unwrap()
+    );
+
+    // a window of 4KiB for only 410 bytes of content
+    let frame = fs::read("./decodecorpus_files/z000090.zst").unwrap();
+    let (header, _) = read_frame_header(frame.as_slice()).unwrap();
+    frame_dec.reset(frame.as_slice()).unwrap();
+    assert_eq!(header.header.window_size().unwrap(), 4096);
+    assert_eq!(frame_dec.effective_window_size(), 410);
+}
+
+#[test]
+fn test_frames() {
+    use crate::frame::{encode_skippable_frame, frames, FrameKind, FrameSpanError};
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use std::fs;
+
+    let first = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    let skippable = encode_skippable_frame(3, b"index of the archive").unwrap();
+    let last = fs::read("./decodecorpus_files/z000022.zst").unwrap();
+    let mut input = first.clone();
+    input.extend_from_slice(&skippable);
+    input.extend_from_slice(&last);
+
+    let spans: Vec<_> = frames(&input).collect::<Result<_, _>>().unwrap();
+    assert_eq!(spans.len(), 3);
+    let skippable_end = first.len() + skippable.len();
+    assert_eq!(spans[0].range, 0..first.len());
+    assert_eq!(spans[1].range, first.len()..skippable_end);
+    assert_eq!(spans[2].range, skippable_end..input.len());
+    assert!(matches!(
+        spans[1].kind,
+        FrameKind::Skippable {
+            magic_number: 0x184D2A53
+        }
+    ));
+
+    let mut frame_dec = FrameDecoder::new();
+    for span in [&spans[0], &spans[2]] {
+        let FrameKind::Zstd { header, blocks } = &span.kind else {
+            panic!("Expected a zstd frame at {:?}", span.range);
+        };
+        let mut source = &input[span.range.clone()];
+        frame_dec.reset(&mut source).unwrap();
+        frame_dec
+            .decode_blocks(&mut source, BlockDecodingStrategy::All)
+            .unwrap();
+        assert!(source.is_empty());
+        assert_eq!(frame_dec.blocks_decoded(), *blocks);
+        assert_eq!(
+            header.descriptor.content_checksum_flag(),
+            frame_dec.get_checksum_from_data().is_some()
+        );
+    }
+
+    // a frame that is cut short ends the iteration with an error
+    let truncated = &input[..input.len() - 10];
+    let mut iter = frames(truncated);
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().unwrap().is_ok());
+    assert!(matches!(
+        iter.next(),
+        Some(Err(FrameSpanError::NotEnoughBytes { .. }))
+    ));
+    assert!(iter.next().is_none());
+
+    assert!(matches!(
+        frames(b"not a frame").next(),
+        Some(Err(FrameSpanError::ReadFrameHeaderError(_)))
+    ));
+    assert!(frames(&[]).next().is_none());
+}
+
+#[test]
+fn test_decode_all_cb() {
+    use crate::frame::encode_skippable_frame;
+    use crate::frame_decoder::{FrameDecoder, FrameDecoderError};
+    use std::fs;
+
+    let mut input = fs::read("./decodecorpus_files/z000088.zst").unwrap();
+    input.extend(encode_skippable_frame(0, b"skipped").unwrap());
+    input.extend(fs::read("./decodecorpus_files/z000033.zst").unwrap());
+    let mut expected = fs::read("./decodecorpus_files/z000088").unwrap();
+    expected.extend(fs::read("./decodecorpus_files/z000033").unwrap());
+
+    let mut frame_dec = FrameDecoder::new();
+
+    // grow the vec by as much as the decoder asks for
+    let mut output = Vec::new();
+    let mut calls = 0;
+    let written = frame_dec
+        .decode_all_cb(&input, &mut output, |output: &mut Vec<u8>, additional| {
+            calls += 1;
+            let len = output.len();
+            output.resize(len + additional, 0);
+            &mut output[len..]
+        })
+        .unwrap();
+    assert_eq!(written, expected.len());
+    assert_eq!(output.len(), written);
+    assert_eq!(output, expected);
+    assert!(calls > 1);
+
+    // hand out less space than requested
+    let mut output = Vec::new();
+    let written = frame_dec
+        .decode_all_cb(&input, &mut output, |output: &mut Vec<u8>, additional| {
+            let len = output.len();
+            output.resize(len + usize::min(additional, 1000), 0);
+            &mut output[len..]
+        })
+        .unwrap();
+    assert_eq!(output, expected);
+    assert_eq!(written, expected.len());
+
+    // hand out more space than requested, only the written part is valid
+    let mut output = Vec::new();
+    let written = frame_dec
+        .decode_all_cb(&input, &mut output, |output: &mut Vec<u8>, additional| {
+            let len = output.len();
+            output.resize(len + additional + 100_000, 0);
+            &mut output[len..]
+        })
+        .unwrap();
+    output.truncate(written);
+    assert_eq!(output, expected);
+
+    let mut output = [0u8; 0];
+    assert!(matches!(
+        frame_dec.decode_all_cb(
+            &input,
+            &mut output,
+            |output: &mut [u8; 0], _| &mut output[..]
+        ),
+        Err(FrameDecoderError::TargetTooSmall)
+    ));
+}
+
+#[test]
+fn test_eight_byte_content_size() {
+    use crate::frame::read_frame_header;
+    use crate::frame_decoder::FrameDecoder;
+
+    let content_size: u64 = 0x1_2345_6789;
+    assert!(content_size > u64::from(u32::MAX));
+
+    // only the header: an eight byte content size and a window of 1MiB
+    let mut header = vec![0x28, 0xb5, 0x2f, 0xfd, 0xc0, 0x50];
+    header.extend_from_slice(&content_size.to_le_bytes());
+
+    let (frame, bytes_read) = read_frame_header(header.as_slice()).unwrap();
+    assert_eq!(bytes_read, 14);
+    assert_eq!(frame.header.descriptor.frame_content_size_flag(), 3);
+    assert_eq!(
+        frame.header.descriptor.frame_content_size_bytes().unwrap(),
+        8
+    );
+    assert_eq!(frame.header.frame_content_size(), content_size);
+
+    let mut frame_dec = FrameDecoder::new();
+    frame_dec.reset(header.as_slice()).unwrap();
+    assert_eq!(frame_dec.current_frame_content_size(), Some(content_size));
+    assert_eq!(frame_dec.effective_window_size(), 1 << 20);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_mixed_checksum_frames() {
+    use crate::streaming_decoder::StreamingDecoder;
+    use std::fs;
+    use std::io::Read;
+
+    let with_check = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    let with_check_content = fs::read("./decodecorpus_files/z000001").unwrap();
+    // single segment frame without a checksum and a raw block with "abc"
+    let without_check = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x20, 0x03, 0x19, 0x00, 0x00, b'a', b'b', b'c',
+    ];
+
+    let mut input = with_check.clone();
+    input.extend_from_slice(&without_check);
+    input.extend_from_slice(&with_check);
+    input.extend_from_slice(&without_check);
+    let expected = [
+        (&with_check_content[..], true),
+        (&b"abc"[..], false),
+        (&with_check_content[..], true),
+        (&b"abc"[..], false),
+    ];
+
+    let mut decoder = StreamingDecoder::new(input.as_slice()).unwrap();
+    for (idx, &(content, has_checksum)) in expected.iter().enumerate() {
+        if idx > 0 {
+            assert!(decoder.next_frame().unwrap());
+        }
+        let mut output = Vec::new();
+        decoder.read_to_end(&mut output).unwrap();
+        assert_eq!(output, content, "frame {}", idx);
+
+        let from_data = decoder.decoder.get_checksum_from_data();
+        assert_eq!(from_data.is_some(), has_checksum, "frame {}", idx);
+        #[cfg(feature = "hash")]
+        if has_checksum {
+            assert_eq!(decoder.decoder.get_calculated_checksum(), from_data);
+        }
+    }
+    assert!(!decoder.next_frame().unwrap());
+}
+
+#[test]
+fn test_decode_slice_to_vec() {
+    use crate::frame_decoder::FrameDecoder;
+    use std::fs;
+
+    let mut state = 3u32;
+    let data: Vec<u8> = (0..4000)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .collect();
+
+    // 1KiB window and a checksum. The raw block bigger than the window and the last raw block skip the decodebuffer,
+    // the small raw block in between does not, because the RLE block after it could reach back into it.
+    let mut frame = vec![0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x00];
+    let mut expected = Vec::new();
+    let blocks: [(u32, &[u8]); 4] = [
+        (0, &data[..2000]),
+        (0, &data[2000..2499]),
+        (1, &data[2499..2500]),
+        (0, &data[2500..]),
+    ];
+    for (idx, &(block_type, content)) in blocks.iter().enumerate() {
+        let last = (idx == blocks.len() - 1) as u32;
+        let size = if block_type == 1 { 300 } else { content.len() };
+        let header = ((size as u32) << 3) | (block_type << 1) | last;
+        frame.extend_from_slice(&header.to_le_bytes()[..3]);
+        frame.extend_from_slice(content);
+        if block_type == 1 {
+            expected.resize(expected.len() + size, content[0]);
+        } else {
+            expected.extend_from_slice(content);
+        }
+    }
+    #[cfg(feature = "hash")]
+    let checksum = {
+        use core::hash::Hasher;
+        let mut hasher = twox_hash::XxHash64::with_seed(0);
+        hasher.write(&expected);
+        hasher.finish() as u32
+    };
+    #[cfg(not(feature = "hash"))]
+    let checksum = 0u32;
+    frame.extend_from_slice(&checksum.to_le_bytes());
+    let frame_len = frame.len();
+    frame.extend_from_slice(b"trailer");
+
+    let mut frame_dec = FrameDecoder::new();
+    let mut output = b"before".to_vec();
+    assert_eq!(
+        frame_dec.decode_slice_to_vec(&frame, &mut output).unwrap(),
+        frame_len
+    );
+    assert_eq!(&output[..6], b"before");
+    assert_eq!(&output[6..], expected.as_slice());
+    assert_eq!(frame_dec.blocks_decoded(), 4);
+    assert_eq!(frame_dec.bytes_read_from_source(), frame_len as u64);
+    assert_eq!(frame_dec.positions().1, expected.len() as u64);
+    assert_eq!(frame_dec.get_checksum_from_data(), Some(checksum));
+    #[cfg(feature = "hash")]
+    assert_eq!(frame_dec.get_calculated_checksum(), Some(checksum));
+
+    // frames of the corpus with compressed blocks in between
+    for file in ["z000022", "z000088", "z000090"] {
+        let frame = fs::read(std::format!("./decodecorpus_files/{}.zst", file)).unwrap();
+        let original = fs::read(std::format!("./decodecorpus_files/{}", file)).unwrap();
+        let mut output = Vec::new();
+        let read = frame_dec.decode_slice_to_vec(&frame, &mut output).unwrap();
+        assert_eq!(read, frame.len());
+        assert_eq!(output, original, "{}", file);
+        #[cfg(feature = "hash")]
+        assert_eq!(
+            frame_dec.get_calculated_checksum(),
+            frame_dec.get_checksum_from_data()
+        );
+    }
+
+    // a raw block that is cut short
+    let mut output = Vec::new();
+    assert!(frame_dec
+        .decode_slice_to_vec(&frame[..frame_len - 100], &mut output)
+        .is_err());
+}
+
+#[test]
+fn test_huffman_build_from_weights() {
+    use crate::huff0::{HuffmanTable, HuffmanTableError};
+
+    // the same table as the directly represented weights 2, 1, 1 in a table description
+    let mut from_weights = HuffmanTable::new();
+    from_weights.build_from_weights(&[2, 1, 1]).unwrap();
+    let mut from_description = HuffmanTable::new();
+    assert_eq!(
+        from_description
+            .build_decoder(&[127 + 3, 0x21, 0x10])
+            .unwrap(),
+        3
+    );
+    assert_eq!(from_weights.describe(), from_description.describe());
+    assert_eq!(from_weights.max_num_bits, 3);
+
+    let mut table = HuffmanTable::new();
+    assert!(matches!(
+        table.build_from_weights(&[]),
+        Err(HuffmanTableError::MissingWeights)
+    ));
+    assert!(matches!(
+        table.build_from_weights(&[0, 0]),
+        Err(HuffmanTableError::MissingWeights)
+    ));
+    // 2 + 2 + 1 leaves 3 for the last symbol, which is not a power of two
+    assert!(matches!(
+        table.build_from_weights(&[2, 2, 1]),
+        Err(HuffmanTableError::LeftoverIsNotAPowerOf2 { got: 3 })
+    ));
+    assert!(matches!(
+        table.build_from_weights(&[1, 12]),
+        Err(HuffmanTableError::WeightBiggerThanMaxNumBits { got: 12 })
+    ));
+    assert!(matches!(
+        table.build_from_weights(&[1; 256]),
+        Err(HuffmanTableError::TooManyWeights { got: 256 })
+    ));
+    // a failed build leaves an empty table behind
+    assert_eq!(table.describe(), "Empty huffman table\n");
+
+    // arbitrary weights never panic
+    let mut state = 1u32;
+    for _ in 0..2000 {
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+        let len = (state >> 16) as usize % 40;
+        let weights: Vec<u8> = (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                (state >> 16) as u8 % 13
+            })
+            .collect();
+        if table.build_from_weights(&weights).is_ok() {
+            assert!(table.max_num_bits > 0);
+        }
+    }
+}
+
+#[test]
+fn test_huffman_from_codes() {
+    use crate::decoding::bit_reader_reverse::BitReaderReversed;
+    use crate::huff0::{HuffmanDecoder, HuffmanTable, HuffmanTableError};
+
+    // the canonical codes the format assigns to the weights 3, 2, 1 and the implied last weight 1
+    let canonical =
+        HuffmanTable::from_codes(vec![(0b1, 1), (0b01, 2), (0b000, 3), (0b001, 3)]).unwrap();
+    let mut from_weights = HuffmanTable::new();
+    from_weights.build_from_weights(&[3, 2, 1]).unwrap();
+    assert_eq!(canonical.describe(), from_weights.describe());
+
+    // encode a message with non canonical codes by hand and decode it again
+    let codes = vec![(0b0, 1), (0b10, 2), (0, 0), (0b110, 3), (0b111, 3)];
+    let table = HuffmanTable::from_codes(codes.clone()).unwrap();
+    assert_eq!(table.max_num_bits, 3);
+    let message = [0u8, 1, 3, 4, 4, 0, 1, 3, 0];
+    // the decoder reads from the end of the stream, first the padding up to a 1 bit, then the codes in order
+    let mut bits = vec![true];
+    for &symbol in &message {
+        let (code, num_bits) = codes[symbol as usize];
+        bits.extend((0..num_bits).rev().map(|bit| code >> bit & 1 == 1));
+    }
+    while bits.len() % 8 != 0 {
+        bits.insert(0, false);
+    }
+    let mut stream: Vec<u8> = bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0, |acc, &bit| (acc << 1) | u8::from(bit)))
+        .collect();
+    stream.reverse();
+    // 4 zero bits and a 1 bit of padding followed by 19 bits of codes
+    assert_eq!(stream, [0b10101100, 0b11011111, 0b00001010]);
+
+    let mut br = BitReaderReversed::new(&stream);
+    let padding = bits.iter().position(|&bit| bit).unwrap() + 1;
+    br.get_bits(padding as u8).unwrap();
+    let mut decoder = HuffmanDecoder::new(&table);
+    decoder.init_state(&mut br).unwrap();
+    let mut decoded = Vec::new();
+    while br.bits_remaining() > -(table.max_num_bits as isize) {
+        decoded.push(decoder.decode_symbol());
+        decoder.next_state(&mut br).unwrap();
+    }
+    assert_eq!(decoded, message);
+
+    assert!(matches!(
+        HuffmanTable::from_codes(vec![(0, 1); 257]),
+        Err(HuffmanTableError::TooManyCodes { got: 257 })
+    ));
+    assert!(matches!(
+        HuffmanTable::from_codes(vec![(0b0, 1), (0b10, 1)]),
+        Err(HuffmanTableError::InvalidCode {
+            symbol: 1,
+            code: 0b10,
+            num_bits: 1
+        })
+    ));
+    assert!(matches!(
+        HuffmanTable::from_codes(vec![(0, 12), (1, 1)]),
+        Err(HuffmanTableError::InvalidCode { symbol: 0, .. })
+    ));
+    assert!(matches!(
+        HuffmanTable::from_codes(vec![(0b1, 1), (0b00, 2), (0b10, 2)]),
+        Err(HuffmanTableError::CodesNotPrefixFree {
+            symbol: 2,
+            other: 0
+        })
+    ));
+    assert!(matches!(
+        HuffmanTable::from_codes(vec![(0b0, 1), (0b10, 2)]),
+        Err(HuffmanTableError::CodesIncomplete {
+            used: 3,
+            available: 4
+        })
+    ));
+    assert!(matches!(
+        HuffmanTable::from_codes(vec![(0, 0), (0, 0)]),
+        Err(HuffmanTableError::CodesIncomplete { used: 0, .. })
+    ));
+}
+
+#[test]
+fn test_fse_compressed_huffman_weights() {
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use crate::huff0::HuffmanTable;
+
+    // 300 literals out of 40 symbols, the lower ones more likely
+    let mut state = 17u32;
+    let mut next = || {
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+        (state >> 16) % 40
+    };
+    let original: Vec<u8> = (0..300)
+        .map(|_| b'0' + u32::min(next(), next()) as u8)
+        .collect();
+    let mut symbols = original.clone();
+    symbols.sort_unstable();
+    symbols.dedup();
+    assert!(symbols.len() > 16);
+
+    // `zstd -19 --no-check` of the original. The huffman table description after the three byte literals header
+    // has too many weights to store them directly, so they are FSE compressed.
+    let frame = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x60, 0x2c, 0x00, 0xed, 0x06, 0x00, 0xc6, 0x52, 0x36, 0x15, 0x40,
+        0xcf, 0x03, 0x8c, 0x14, 0x2e, 0x6b, 0xe6, 0x72, 0x76, 0xbb, 0x9b, 0xe4, 0x38, 0xdd, 0x33,
+        0xc3, 0x30, 0x0c, 0x63, 0x4c, 0x2f, 0x00, 0x30, 0x00, 0x2f, 0x00, 0x31, 0x56, 0x44, 0xf9,
+        0xf7, 0x30, 0x47, 0xf8, 0xcb, 0x36, 0x76, 0x5f, 0x32, 0xca, 0xc5, 0x72, 0x82, 0x78, 0x72,
+        0x15, 0xce, 0xf2, 0x8c, 0xfa, 0x4e, 0x38, 0xbb, 0x00, 0x4f, 0x9c, 0xf6, 0x7d, 0x2d, 0x35,
+        0x8b, 0x97, 0x73, 0x7e, 0x06, 0xb2, 0x11, 0x29, 0xd9, 0x17, 0xd0, 0xbc, 0x38, 0x4a, 0x11,
+        0x63, 0x72, 0xa8, 0xe3, 0x56, 0x99, 0xf8, 0xe1, 0x23, 0x30, 0x05, 0x12, 0x56, 0xa3, 0xf9,
+        0x12, 0x92, 0x6d, 0xb6, 0x38, 0x00, 0x68, 0x93, 0xb8, 0x06, 0xed, 0x44, 0x35, 0x75, 0xc6,
+        0x5a, 0x8c, 0xc9, 0xca, 0xb0, 0x8d, 0xa9, 0xb8, 0xef, 0xec, 0xce, 0x7f, 0xeb, 0x11, 0x0c,
+        0xc9, 0x3e, 0x9b, 0xd5, 0x5b, 0x8c, 0x0a, 0x48, 0xf7, 0xff, 0x61, 0xf6, 0x37, 0xac, 0x85,
+        0x37, 0xee, 0x88, 0x0e, 0xef, 0x54, 0x50, 0xd1, 0xe9, 0x89, 0x3d, 0xf8, 0x84, 0x1d, 0x64,
+        0x15, 0x97, 0x24, 0x98, 0x60, 0xd8, 0xb4, 0xda, 0x51, 0x5b, 0x12, 0x53, 0x93, 0x75, 0x3e,
+        0xb3, 0x88, 0x0a, 0xfe, 0x07, 0x47, 0xa9, 0xae, 0xc0, 0x31, 0x88, 0x5d, 0x8b, 0xc5, 0x66,
+        0x31, 0xb0, 0xe8, 0x21, 0xfa, 0xbe, 0x1b, 0x47, 0x2b, 0x66, 0xa9, 0x12, 0xbb, 0x79, 0x43,
+        0x2a, 0xdf, 0xf9, 0x57, 0x3f, 0x9e, 0xba, 0x64, 0xd7, 0xfe, 0x4b, 0xba, 0x36, 0xfa, 0x1a,
+        0x67, 0x20, 0xed, 0x18, 0x13, 0x00,
+    ];
+    let description = &frame[13..];
+    assert!(description[0] < 128);
+    let mut table = HuffmanTable::new();
+    assert_eq!(
+        table.build_decoder(description).unwrap(),
+        1 + u32::from(description[0])
+    );
+    // two header lines and one line per symbol
+    assert_eq!(table.describe().lines().count(), 2 + symbols.len());
+
+    let mut frame_dec = FrameDecoder::new();
+    let mut source = &frame[..];
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert!(source.is_empty());
+    assert_eq!(frame_dec.collect().unwrap(), original);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_block_entropy() {
+    use crate::huff0::block_entropy;
+
+    let mut state = 5u32;
+    let random: Vec<u8> = (0..128 * 1024)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .collect();
+    let entropy = block_entropy(&random);
+    assert!(entropy > 7.99 && entropy <= 8.0, "{}", entropy);
+
+    assert_eq!(block_entropy(&[7; 1000]), 0.0);
+    assert_eq!(block_entropy(&[]), 0.0);
+    let entropy = block_entropy(b"abcdefgh");
+    assert!((entropy - 3.0).abs() < 1e-9, "{}", entropy);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_streaming_max_output_size() {
+    use crate::streaming_decoder::StreamingDecoder;
+    use std::io::Read;
+
+    // 100 RLE blocks of 128KiB each, a bit over 12MiB from a few hundred bytes
+    let mut bomb = vec![0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x38];
+    for idx in 0..100 {
+        let last = u32::from(idx == 99);
+        let header = ((128 * 1024) << 3) | (1 << 1) | last;
+        bomb.extend_from_slice(&header.to_le_bytes()[..3]);
+        bomb.push(b'x');
+    }
+
+    let mut stream = StreamingDecoder::new(bomb.as_slice()).unwrap();
+    stream.set_max_output_size(Some(1024 * 1024));
+    let mut result = Vec::new();
+    assert!(stream.read_to_end(&mut result).is_err());
+    assert_eq!(result.len(), 1024 * 1024);
+    assert!(result.iter().all(|&b| b == b'x'));
+    // decoding stopped right after the limit
+    assert!(stream.decoder.bytes_read_from_source() < bomb.len() as u64 / 2);
+    assert!(stream.read(&mut [0; 1]).is_err());
+    // reading nothing does not error
+    assert_eq!(stream.read(&mut []).unwrap(), 0);
+
+    // output exactly at the limit is fine, over all frames
+    let mut two_frames = bomb.clone();
+    two_frames.extend_from_slice(&bomb);
+    let mut stream = StreamingDecoder::new(two_frames.as_slice()).unwrap();
+    stream.set_max_output_size(Some(2 * 100 * 128 * 1024));
+    let mut result = Vec::new();
+    stream.read_to_end(&mut result).unwrap();
+    assert!(stream.next_frame().unwrap());
+    stream.read_to_end(&mut result).unwrap();
+    assert_eq!(result.len(), 2 * 100 * 128 * 1024);
+    assert!(!stream.next_frame().unwrap());
+
+    let mut stream = StreamingDecoder::new(two_frames.as_slice()).unwrap();
+    stream.set_max_output_size(Some(100 * 128 * 1024 + 1));
+    let mut result = Vec::new();
+    stream.read_to_end(&mut result).unwrap();
+    assert!(stream.next_frame().unwrap());
+    assert!(stream.read_to_end(&mut result).is_err());
+    assert_eq!(result.len(), 100 * 128 * 1024 + 1);
+
+    // without a limit everything is decoded
+    let mut stream = StreamingDecoder::new(bomb.as_slice()).unwrap();
+    stream.set_max_output_size(Some(1));
+    stream.set_max_output_size(None);
+    let mut result = Vec::new();
+    stream.read_to_end(&mut result).unwrap();
+    assert_eq!(result.len(), 100 * 128 * 1024);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_decode_prefix() {
+    use crate::frame_decoder::FrameDecoder;
+    use std::fs;
+
+    // a frame with many blocks and a window smaller than its content
+    let input = fs::read("./decodecorpus_files/z000033.zst").unwrap();
+    let original = fs::read("./decodecorpus_files/z000033").unwrap();
+    let mut frame_dec = FrameDecoder::new();
+    let mut full = Vec::new();
+    frame_dec.decode_slice_to_vec(&input, &mut full).unwrap();
+    assert_eq!(full, original);
+
+    for n in [0, 1, 1000, 200_000, 500_000, full.len() - 1, full.len()] {
+        let prefix = frame_dec.decode_prefix(&input, n).unwrap();
+        assert_eq!(prefix, &full[..n], "prefix of {} bytes", n);
+        // decoding stopped after the block that reached n bytes
+        if n < 200_000 {
+            assert!(frame_dec.bytes_read_from_source() < input.len() as u64 / 2);
+            assert!(!frame_dec.is_finished());
+        }
+    }
+
+    // more bytes than the frame has return all of them
+    let prefix = frame_dec.decode_prefix(&input, full.len() + 1000).unwrap();
+    assert_eq!(prefix, full);
+    assert!(frame_dec.is_finished());
+    let prefix = frame_dec.decode_prefix(&input, usize::MAX).unwrap();
+    assert_eq!(prefix, full);
+}
+
+#[test]
+fn test_single_segment_window() {
+    use crate::frame::read_frame_header;
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+
+    // 64 random bytes, 200KiB of runs and the random bytes again, so the last match reaches back over the whole
+    // content. `zstd -3` stores this as a single segment frame
+    let mut x: u32 = 7;
+    let random: Vec<u8> = (0..64)
+        .map(|_| {
+            x = x.wrapping_mul(1103515245).wrapping_add(12345);
+            (x >> 16) as u8
+        })
+        .collect();
+    let mut original = random.clone();
+    original.extend((0..200 * 1024).map(|idx| (idx / 4096) as u8));
+    original.extend_from_slice(&random);
+    let frame = [
+        0x28, 0xb5, 0x2f, 0xfd, 0xa4, 0x80, 0x20, 0x03, 0x00, 0x2c, 0x05, 0x00, 0x04, 0x06, 0x6c,
+        0x4e, 0x74, 0x92, 0x13, 0x25, 0x22, 0x2e, 0x31, 0xa1, 0xcd, 0x13, 0xbe, 0x12, 0xed, 0x42,
+        0x69, 0x66, 0xce, 0x24, 0xfc, 0x23, 0xd7, 0xda, 0x8d, 0x20, 0x97, 0x61, 0x6a, 0x06, 0x95,
+        0x6e, 0xc2, 0x8a, 0xd4, 0x03, 0x13, 0x68, 0x28, 0xd4, 0x57, 0x1e, 0x3c, 0x5d, 0xee, 0x6e,
+        0x5e, 0xc0, 0x4a, 0x91, 0x11, 0x5f, 0x5d, 0x3b, 0x51, 0x3e, 0xc2, 0x53, 0xa4, 0x16, 0xad,
+        0x6e, 0xe5, 0x38, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+        0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a,
+        0x1b, 0x1c, 0x1d, 0x1e, 0x1f, 0x20, 0x14, 0x00, 0x2f, 0xbc, 0x17, 0xfe, 0x1f, 0xff, 0x19,
+        0xff, 0x85, 0xff, 0xc7, 0x7f, 0xc6, 0x7f, 0xe1, 0xff, 0xf1, 0x9f, 0xf1, 0x5f, 0xf8, 0x7f,
+        0xfc, 0x67, 0xfc, 0x17, 0xfe, 0x1f, 0xff, 0x19, 0xff, 0x85, 0xff, 0xc7, 0x7f, 0xc6, 0x7f,
+        0xe1, 0xff, 0xf1, 0x9f, 0xf1, 0x5f, 0xf8, 0x7f, 0xfc, 0x67, 0xfc, 0x17, 0xfe, 0x1f, 0xff,
+        0x19, 0xff, 0x85, 0xff, 0xc7, 0x7f, 0xc6, 0x7f, 0x09, 0xf8, 0x6f, 0x06, 0xcd, 0x02, 0x00,
+        0x98, 0x1f, 0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2a, 0x2b, 0x2c,
+        0x2d, 0x2e, 0x2f, 0x30, 0x31, 0x14, 0x00, 0x1d, 0x02, 0x19, 0x25, 0xe0, 0x3f, 0xf0, 0x8f,
+        0xff, 0xc0, 0x67, 0xfc, 0x07, 0xbe, 0xf0, 0x1f, 0xf8, 0xc7, 0x7f, 0xe0, 0x33, 0xfe, 0x03,
+        0x5f, 0xf8, 0x0f, 0xfc, 0xe3, 0x3f, 0xf0, 0x19, 0xff, 0x81, 0x2f, 0xfc, 0x07, 0xfe, 0xf1,
+        0x1f, 0xf8, 0x8c, 0xff, 0xc0, 0x17, 0xfe, 0x03, 0xff, 0xf8, 0x0f, 0x7c, 0xc6, 0x7f, 0xe0,
+        0x0b, 0xff, 0x81, 0x7f, 0xfc, 0x07, 0x3e, 0xe3, 0x3f, 0xf0, 0x85, 0x12, 0xb8, 0x02, 0x1f,
+        0xbb, 0xc9, 0xfe,
+    ];
+
+    // there is no window descriptor, the window is as big as the content
+    let (header, _) = read_frame_header(&frame[..]).unwrap();
+    assert!(header.header.descriptor.single_segment_flag());
+    assert_eq!(header.header.frame_content_size(), original.len() as u64);
+    assert_eq!(header.header.window_size().unwrap(), original.len() as u64);
+
+    // collecting while decoding must keep the whole window
+    let mut frame_dec = FrameDecoder::new();
+    let mut source = &frame[..];
+    frame_dec.reset(&mut source).unwrap();
+    let mut output = Vec::new();
+    while !frame_dec.is_finished() {
+        frame_dec
+            .decode_blocks(&mut source, BlockDecodingStrategy::UptoBytes(16 * 1024))
+            .unwrap();
+        output.extend(frame_dec.collect().unwrap_or_default());
+    }
+    output.extend(frame_dec.collect().unwrap_or_default());
+    assert_eq!(output, original);
+
+    #[cfg(feature = "hash")]
+    assert_eq!(
+        frame_dec.get_checksum_from_data(),
+        frame_dec.get_calculated_checksum()
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_window_bigger_than_content() {
+    use crate::frame::read_frame_header;
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use crate::streaming_decoder::StreamingDecoder;
+    use std::fs;
+    use std::io::Read;
+
+    // 256 times the same byte in a single compressed block, declaring a window of 64MB and a checksum
+    let frame = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x44, 0x80, 0x00, 0x00, 0x4d, 0x00, 0x00, 0x10, 0x7a, 0x7a, 0x01,
+        0x00, 0x7b, 0x0a, 0x60, 0x01, 0xd9, 0xd6, 0x14, 0x4d,
+    ];
+    // a raw block of 5 bytes, declaring a window of 64MB
+    let raw = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x80, 0x80, 0x05, 0x00, 0x00, 0x00, 0x29, 0x00, 0x00, 0x68, 0x65,
+        0x6c, 0x6c, 0x6f,
+    ];
+    let frames: [(&[u8], &[u8]); 2] = [(&frame, &[b'z'; 256]), (&raw, b"hello")];
+
+    // the buffers of the first frame are reused for the following ones
+    let mut frame_dec = FrameDecoder::new();
+    let corpus = fs::read("./decodecorpus_files/z000088.zst").unwrap();
+    let mut source = corpus.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    frame_dec.collect().unwrap();
+
+    for (content, original) in frames {
+        let (header, _) = read_frame_header(content).unwrap();
+        assert_eq!(header.header.window_size().unwrap(), 64 * 1024 * 1024);
+        assert_eq!(header.header.frame_content_size(), original.len() as u64);
+
+        let mut source = content;
+        frame_dec.reset(&mut source).unwrap();
+        frame_dec
+            .decode_blocks(&mut source, BlockDecodingStrategy::All)
+            .unwrap();
+        assert!(source.is_empty());
+        assert!(frame_dec.is_finished());
+        assert_eq!(frame_dec.collect().unwrap(), original);
+
+        let mut output = Vec::new();
+        let mut stream_dec = StreamingDecoder::new(content).unwrap();
+        stream_dec.read_to_end(&mut output).unwrap();
+        assert_eq!(output, original);
+    }
+}
+
+#[test]
+fn test_tiny_frames() {
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+
+    // produced by `zstd -1` for inputs of 0, 1 and 2 bytes
+    let empty = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x24, 0x00, 0x01, 0x00, 0x00, 0x99, 0xe9, 0xd8, 0x51,
+    ];
+    let one = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x24, 0x01, 0x09, 0x00, 0x00, 0x61, 0x5b, 0x6e, 0x8c, 0xa9,
+    ];
+    let two = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x24, 0x02, 0x11, 0x00, 0x00, 0x61, 0x62, 0x61, 0x4a, 0xd0, 0x92,
+    ];
+    // 256 times the same byte, this is a compressed block containing a single sequence
+    let repeated = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x64, 0x00, 0x00, 0x4d, 0x00, 0x00, 0x10, 0x7a, 0x7a, 0x01, 0x00,
+        0x7b, 0x0a, 0x60, 0x01, 0xd9, 0xd6, 0x14, 0x4d,
+    ];
+    // the bytes 0..=255 end up in a single raw block
+    let mut counting = vec![0x28, 0xb5, 0x2f, 0xfd, 0x64, 0x00, 0x00, 0x01, 0x08, 0x00];
+    counting.extend(0..=255u8);
+    counting.extend_from_slice(&[0x4b, 0x90, 0xcd, 0x06]);
+
+    let counting_original: Vec<u8> = (0..=255u8).collect();
+    let frames: [(&[u8], &[u8]); 5] = [
+        (&empty, b""),
+        (&one, b"a"),
+        (&two, b"ab"),
+        (&repeated, &[b'z'; 256]),
+        (&counting, &counting_original),
+    ];
+
+    let mut frame_dec = FrameDecoder::new();
+    for (content, original) in frames {
+        let mut source = content;
+        frame_dec.reset(&mut source).unwrap();
+        frame_dec
+            .decode_blocks(&mut source, BlockDecodingStrategy::All)
+            .unwrap();
+        assert!(source.is_empty());
+        assert_eq!(frame_dec.collect().unwrap(), original);
+        #[cfg(feature = "hash")]
+        assert_eq!(
+            frame_dec.get_calculated_checksum(),
+            frame_dec.get_checksum_from_data()
+        );
+    }
+}
+
+#[test]
+fn test_raw_blocks() {
+    use crate::blocks::block::BlockType;
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+
+    let data: Vec<u8> = (0..300 * 1024u32).map(|x| (x * 7 + x / 13) as u8).collect();
+    // maximum size blocks, an empty block and a small last block
+    let frame = single_segment_frame(&[
+        (BlockType::Raw, &data[..128 * 1024]),
+        (BlockType::Raw, &data[128 * 1024..256 * 1024]),
+        (BlockType::Raw, &[]),
+        (BlockType::Raw, &data[256 * 1024..]),
+    ]);
+
+    let mut frame_dec = FrameDecoder::new();
+    let mut result = Vec::new();
+    let mut source = frame.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    while !frame_dec.is_finished() {
+        frame_dec
+            .decode_blocks(&mut source, BlockDecodingStrategy::UptoBlocks(1))
+            .unwrap();
+        result.extend(frame_dec.collect().unwrap_or_default());
+    }
+    assert_eq!(frame_dec.blocks_decoded(), 4);
+    assert_eq!(frame_dec.bytes_read_from_source(), frame.len() as u64);
+    assert_eq!(result, data);
+
+    // a raw block that is cut short
+    let mut source = &frame[..100 * 1024];
+    frame_dec.reset(&mut source).unwrap();
+    assert!(frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .is_err());
+}
+
+#[test]
+fn test_count_blocks() {
+    use crate::frame_decoder::{count_blocks, BlockDecodingStrategy, FrameDecoder};
+    use std::fs;
+
+    let mut frame_dec = FrameDecoder::new();
+    for file in ["z000001", "z000022", "z000088", "z000090"] {
+        let content = fs::read(std::format!("./decodecorpus_files/{}.zst", file)).unwrap();
+
+        let mut source = content.as_slice();
+        frame_dec.reset(&mut source).unwrap();
+        frame_dec
+            .decode_blocks(&mut source, BlockDecodingStrategy::All)
+            .unwrap();
+        assert_eq!(count_blocks(&content).unwrap(), frame_dec.blocks_decoded());
+    }
+
+    let content = fs::read("./decodecorpus_files/z000022.zst").unwrap();
+    assert!(count_blocks(&content).unwrap() > 1);
+    assert!(count_blocks(&content[..content.len() / 2]).is_err());
+
+    let skippable = crate::frame::encode_skippable_frame(3, b"metadata").unwrap();
+    assert!(matches!(
+        count_blocks(&skippable),
+        Err(crate::frame::FrameSpanError::ReadFrameHeaderError(
+            crate::frame::ReadFrameHeaderError::SkipFrame { length: 8, .. }
+        ))
+    ));
+}
+
+#[test]
+fn test_decoding_strategies() {
+    use crate::blocks::block::BlockType;
+    use crate::decoding::block_decoder;
+    use crate::decoding::scratch::DecoderScratch;
+    use crate::frame::read_frame_header;
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use std::fs;
+
+    let content = fs::read("./decodecorpus_files/z000022.zst").unwrap();
+    let original = fs::read("./decodecorpus_files/z000022").unwrap();
+
+    // decode the blocks one by one to learn how many sequences and bytes each of them contains
+    let mut source = content.as_slice();
+    let (frame, _) = read_frame_header(&mut source).unwrap();
+    let mut scratch = DecoderScratch::new(frame.header.window_size().unwrap() as usize);
+    let mut block_dec = block_decoder::new();
+    let mut blocks = Vec::new();
+    loop {
+        let (header, _) = block_dec.read_block_header(&mut source).unwrap();
+        let len_before = scratch.buffer.len();
+        block_dec
+            .decode_block_content(&header, &mut scratch, &mut source)
+            .unwrap();
+        let sequences = match header.block_type {
+            BlockType::Compressed => scratch.sequences.len(),
+            _ => 0,
+        };
+        blocks.push((sequences, scratch.buffer.len() - len_before));
+        if header.last_block {
+            break;
+        }
+    }
+    assert!(blocks.len() > 3);
+    assert!(blocks.iter().any(|&(sequences, _)| sequences > 0));
+
+    // how many blocks a call has to decode to fulfill the strategy when starting at a block
+    let blocks_per_call = |strategy: &BlockDecodingStrategy, start: usize| -> usize {
+        let mut sequences = 0;
+        let mut bytes = 0;
+        for (idx, &(block_sequences, block_bytes)) in blocks[start..].iter().enumerate() {
+            sequences += block_sequences;
+            bytes += block_bytes;
+            let done = match *strategy {
+                BlockDecodingStrategy::All => false,
+                BlockDecodingStrategy::UptoBlocks(n) => idx + 1 >= n,
+                BlockDecodingStrategy::UptoBytes(n) => bytes >= n,
+                BlockDecodingStrategy::UptoSequences(n) => sequences >= n,
+            };
+            if done {
+                return idx + 1;
+            }
+        }
+        blocks.len() - start
+    };
+
+    let strategies: [fn() -> BlockDecodingStrategy; 7] = [
+        || BlockDecodingStrategy::UptoBlocks(1),
+        || BlockDecodingStrategy::UptoBlocks(2),
+        || BlockDecodingStrategy::UptoBytes(1),
+        || BlockDecodingStrategy::UptoBytes(100 * 1024),
+        || BlockDecodingStrategy::UptoSequences(1),
+        || BlockDecodingStrategy::UptoSequences(40000),
+        || BlockDecodingStrategy::All,
+    ];
+    let mut frame_dec = FrameDecoder::new();
+    for strategy in strategies {
+        let mut source = content.as_slice();
+        frame_dec.reset(&mut source).unwrap();
+        let mut output = Vec::new();
+        while !frame_dec.is_finished() {
+            let start = frame_dec.blocks_decoded();
+            let expected_blocks = blocks_per_call(&strategy(), start);
+            frame_dec.decode_blocks(&mut source, strategy()).unwrap();
+            assert_eq!(frame_dec.blocks_decoded() - start, expected_blocks);
+            output.extend(frame_dec.collect().unwrap_or_default());
+        }
+        output.extend(frame_dec.collect().unwrap_or_default());
+        assert_eq!(output, original);
+    }
+}
 
-#[cfg(test)]
-extern crate std;
+#[test]
+fn test_push_decoder() {
+    use crate::push_decoder::{ChunkStatus, PushDecoder};
+    use std::fs;
 
-#[cfg(all(test, not(feature = "std")))]
-impl crate::io_nostd::Read for std::fs::File {
-    fn read(&mut self, buf: &mut [u8]) -> Result<usize, crate::io_nostd::Error> {
-        std::io::Read::read(self, buf).map_err(|e| {
-            if e.get_ref().is_none() {
-                crate::io_nostd::Error::from(crate::io_nostd::ErrorKind::Other)
-            } else {
-                crate::io_nostd::Error::new(
-                    crate::io_nostd::ErrorKind::Other,
-                    alloc::boxed::Box::new(e.into_inner().unwrap()),
-                )
+    let mut decoder = PushDecoder::new();
+    for file in ["z000001", "z000088", "z000090"] {
+        let content = fs::read(std::format!("./decodecorpus_files/{}.zst", file)).unwrap();
+        let original = fs::read(std::format!("./decodecorpus_files/{}", file)).unwrap();
+
+        // the frame followed by the start of another one
+        let mut input = content.clone();
+        input.extend_from_slice(&crate::MAGIC_NUM.to_le_bytes());
+
+        let mut output = Vec::new();
+        let mut fed = 0;
+        let mut starved = 0;
+        loop {
+            let (consumed, status) = decoder.decode_chunk(&input[fed..fed + 1]).unwrap();
+            fed += consumed;
+            match status {
+                ChunkStatus::Output(decoded) => {
+                    assert_eq!(consumed, 1);
+                    output.extend(decoded);
+                }
+                ChunkStatus::NeedMoreInput => {
+                    assert_eq!(consumed, 1);
+                    starved += 1;
+                }
+                ChunkStatus::FrameFinished(decoded) => {
+                    output.extend(decoded);
+                    break;
+                }
             }
-        })
+        }
+        assert_eq!(fed, content.len());
+        assert!(starved > 0);
+        assert_eq!(output, original);
+
+        // the bytes after the frame are not consumed
+        let (consumed, status) = decoder.decode_chunk(&[]).unwrap();
+        assert_eq!((consumed, status), (0, ChunkStatus::NeedMoreInput));
+        let (consumed, status) = decoder.decode_chunk(&input[fed..]).unwrap();
+        assert_eq!((consumed, status), (4, ChunkStatus::NeedMoreInput));
+        decoder = PushDecoder::new();
     }
+
+    // two frames in one chunk
+    let first = fs::read("./decodecorpus_files/z000090.zst").unwrap();
+    let second = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    let mut input = first.clone();
+    input.extend_from_slice(&second);
+    let (consumed, status) = decoder.decode_chunk(&input).unwrap();
+    assert_eq!(consumed, first.len());
+    assert_eq!(
+        status,
+        ChunkStatus::FrameFinished(fs::read("./decodecorpus_files/z000090").unwrap())
+    );
+    let (consumed, status) = decoder.decode_chunk(&input[consumed..]).unwrap();
+    assert_eq!(consumed, second.len());
+    assert_eq!(
+        status,
+        ChunkStatus::FrameFinished(fs::read("./decodecorpus_files/z000001").unwrap())
+    );
+
+    let err = decoder.decode_chunk(b"not a zstd frame").unwrap_err();
+    assert_eq!(err.consumed, 0);
 }
 
-#[cfg(all(test, feature = "std"))]
-#[allow(dead_code)]
-fn assure_error_impl() {
-    // not a real test just there to throw an compiler error if Error is not derived correctly
+#[test]
+fn test_push_decoder_skippable_frames() {
+    use crate::push_decoder::{ChunkStatus, PushDecoder};
+    use std::fs;
 
-    use crate::frame_decoder::FrameDecoderError;
-    let _err: &dyn std::error::Error = &FrameDecoderError::NotYetInitialized;
+    let first = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    let second = fs::read("./decodecorpus_files/z000090.zst").unwrap();
+    let mut skippable = 0x184D2A53u32.to_le_bytes().to_vec();
+    skippable.extend_from_slice(&1000u32.to_le_bytes());
+    skippable.extend_from_slice(&[0xAB; 1000]);
+    let mut input = first.clone();
+    input.extend_from_slice(&skippable);
+    input.extend_from_slice(&second);
+
+    // fed one byte at a time
+    let mut decoder = PushDecoder::new();
+    let mut frames = Vec::new();
+    let mut output = Vec::new();
+    let mut fed = 0;
+    while fed < input.len() {
+        let (consumed, status) = decoder.decode_chunk(&input[fed..fed + 1]).unwrap();
+        assert_eq!(consumed, 1);
+        fed += consumed;
+        match status {
+            ChunkStatus::Output(decoded) => output.extend(decoded),
+            ChunkStatus::NeedMoreInput => {}
+            ChunkStatus::FrameFinished(decoded) => {
+                output.extend(decoded);
+                frames.push(core::mem::take(&mut output));
+            }
+        }
+    }
+    assert_eq!(
+        frames,
+        [
+            fs::read("./decodecorpus_files/z000001").unwrap(),
+            fs::read("./decodecorpus_files/z000090").unwrap()
+        ]
+    );
+
+    // a skippable frame is skipped within a chunk as well
+    let (consumed, status) = decoder.decode_chunk(&input[first.len()..]).unwrap();
+    assert_eq!(consumed, skippable.len() + second.len());
+    assert_eq!(
+        status,
+        ChunkStatus::FrameFinished(fs::read("./decodecorpus_files/z000090").unwrap())
+    );
+
+    // the bytes before the broken frame were consumed
+    let mut broken = skippable.clone();
+    broken.extend_from_slice(b"not a zstd frame");
+    let err = decoder.decode_chunk(&broken).unwrap_err();
+    assert_eq!(err.consumed, skippable.len());
+    assert!(matches!(
+        err.source,
+        crate::frame_decoder::FrameDecoderError::ReadFrameHeaderError(
+            crate::frame::ReadFrameHeaderError::BadMagicNumber(_)
+        )
+    ));
+    // the decoder starts over with the next frame
+    let (consumed, status) = decoder.decode_chunk(&second).unwrap();
+    assert_eq!(consumed, second.len());
+    assert!(matches!(status, ChunkStatus::FrameFinished(_)));
 }
 
-#[cfg(all(test, feature = "std"))]
-#[allow(dead_code)]
-fn assure_decoder_send_sync() {
-    // not a real test just there to throw an compiler error if FrameDecoder is Send + Sync
+#[test]
+fn test_positions() {
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use std::fs;
 
-    use crate::frame_decoder::FrameDecoder;
-    let decoder = FrameDecoder::new();
-    std::thread::spawn(move || {
-        drop(decoder);
-    });
+    let mut frame_dec = FrameDecoder::new();
+    assert_eq!(frame_dec.positions(), (0, 0));
+
+    for file in ["z000022", "z000001"] {
+        let content = fs::read(std::format!("./decodecorpus_files/{}.zst", file)).unwrap();
+        let original = fs::read(std::format!("./decodecorpus_files/{}", file)).unwrap();
+
+        let mut source = content.as_slice();
+        frame_dec.reset(&mut source).unwrap();
+        let (header_len, decoded) = frame_dec.positions();
+        assert_eq!(header_len, (content.len() - source.len()) as u64);
+        assert_eq!(decoded, 0);
+
+        let mut result = Vec::new();
+        let mut last = frame_dec.positions();
+        while !frame_dec.is_finished() {
+            frame_dec
+                .decode_blocks(&mut source, BlockDecodingStrategy::UptoBlocks(1))
+                .unwrap();
+            // collecting does not change the positions
+            result.extend(frame_dec.collect().unwrap_or_default());
+            let positions = frame_dec.positions();
+            assert!(positions.0 > last.0);
+            assert!(positions.1 >= last.1);
+            assert_eq!(positions.0, (content.len() - source.len()) as u64);
+            last = positions;
+        }
+        assert_eq!(last, (content.len() as u64, original.len() as u64));
+        assert_eq!(result, original);
+    }
 }
 
 #[test]
-fn skippable_frame() {
-    use crate::frame;
+fn test_decode_one_frame() {
+    use crate::frame_decoder::decode_one_frame;
+    use std::fs;
 
-    let mut content = vec![];
-    content.extend_from_slice(&0x184D2A50u32.to_le_bytes());
-    content.extend_from_slice(&300u32.to_le_bytes());
-    assert_eq!(8, content.len());
-    let err = frame::read_frame_header(content.as_slice());
+    for file in ["z000001", "z000022", "z000088", "z000090"] {
+        let content = fs::read(std::format!("./decodecorpus_files/{}.zst", file)).unwrap();
+        let original = fs::read(std::format!("./decodecorpus_files/{}", file)).unwrap();
+
+        let (decoded, rest) = decode_one_frame(&content).unwrap();
+        assert_eq!(decoded, original);
+        assert!(rest.is_empty());
+
+        // arbitrary trailing bytes, including another frame, are left alone
+        let trailers: [&[u8]; 3] = [b"\x00", b"some trailing bytes", &content];
+        for trailer in trailers {
+            let mut input = content.clone();
+            input.extend_from_slice(trailer);
+            let (decoded, rest) = decode_one_frame(&input).unwrap();
+            assert_eq!(decoded, original);
+            assert_eq!(rest, trailer);
+        }
+
+        assert!(decode_one_frame(&content[..content.len() - 1]).is_err());
+    }
+    assert!(decode_one_frame(&[]).is_err());
+}
+
+#[test]
+fn test_validate_frame() {
+    use crate::frame_decoder::{validate_frame, FrameDecoderError};
+    use std::fs;
+
+    for file in ["z000001", "z000022", "z000088", "z000090"] {
+        let content = fs::read(std::format!("./decodecorpus_files/{}.zst", file)).unwrap();
+        validate_frame(&content).unwrap();
+        // a frame that is cut short is not valid
+        assert!(validate_frame(&content[..content.len() - 1]).is_err());
+        assert!(validate_frame(&content[..content.len() / 2]).is_err());
+    }
+    assert!(validate_frame(&[]).is_err());
+
+    // z000090.zst declares a content size of 410 bytes, make it declare 411
+    let mut content = fs::read("./decodecorpus_files/z000090.zst").unwrap();
+    assert_eq!(content[6], 0x9a);
+    content[6] = 0x9b;
     assert!(matches!(
-        err,
-        Err(frame::ReadFrameHeaderError::SkipFrame {
-            magic_number: 0x184D2A50u32,
-            length: 300
+        validate_frame(&content),
+        Err(FrameDecoderError::ContentSizeMismatch {
+            declared: 411,
+            decoded: 410
         })
     ));
 
-    content.clear();
-    content.extend_from_slice(&0x184D2A5Fu32.to_le_bytes());
-    content.extend_from_slice(&0xFFFFFFFFu32.to_le_bytes());
-    assert_eq!(8, content.len());
-    let err = frame::read_frame_header(content.as_slice());
-    assert!(matches!(
-        err,
-        Err(frame::ReadFrameHeaderError::SkipFrame {
-            magic_number: 0x184D2A5Fu32,
-            length: 0xFFFFFFFF
+    // z000001.zst has a checksum
+    #[cfg(feature = "hash")]
+    {
+        let mut content = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+        let last = content.len() - 1;
+        content[last] ^= 0xff;
+        assert!(matches!(
+            validate_frame(&content),
+            Err(FrameDecoderError::ChecksumMismatch { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_repeat_offsets_without_literals() {
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+
+    // produced by `zstd -19 --no-check`, contains sequences without literals that use all three repeated offsets,
+    // including the first repeated offset minus 1
+    let frame = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x68, 0x6d, 0x02, 0x00, 0xe4, 0x02, 0xcf, 0xe4, 0x49, 0xd8,
+        0xb9, 0x4e, 0x5e, 0x14, 0x88, 0x98, 0x28, 0x78, 0xe6, 0x8c, 0xdb, 0x55, 0x9d, 0x8f, 0x75,
+        0xeb, 0xe6, 0x1d, 0x33, 0x74, 0xfe, 0xb4, 0x82, 0xfc, 0xb0, 0x4e, 0xa2, 0x21, 0x58, 0x04,
+        0xac, 0x67, 0x46, 0x5c, 0xcf, 0xe4, 0x49, 0xd8, 0xb9, 0x4e, 0x5e, 0x14, 0x0d, 0x88, 0x10,
+        0x37, 0xcb, 0x9e, 0x03, 0x10, 0xce, 0xeb, 0xdf, 0x03, 0x2b, 0x88, 0xea, 0xcc, 0xc2, 0x00,
+        0x58, 0x06, 0xa9, 0xf2, 0xa6, 0xa9, 0xa8, 0x0a, 0x32, 0x5f, 0x1b,
+    ];
+    let original = [
+        0xcf, 0xe4, 0x49, 0xd8, 0xb9, 0x4e, 0x5e, 0x14, 0xcf, 0xe4, 0x49, 0xd8, 0xb9, 0x4e, 0x5e,
+        0x14, 0x88, 0x98, 0x28, 0x78, 0xe6, 0x8c, 0xdb, 0x55, 0x9d, 0x8f, 0x75, 0xeb, 0xe6, 0x1d,
+        0x33, 0x74, 0xfe, 0x8f, 0x75, 0xeb, 0xe6, 0x1d, 0x33, 0x74, 0xfe, 0xb4, 0xcf, 0xe4, 0x49,
+        0xd8, 0xb9, 0x4e, 0x5e, 0x14, 0x8f, 0x75, 0xeb, 0xe6, 0x1d, 0x33, 0x74, 0xfe, 0xcf, 0xe4,
+        0x49, 0xd8, 0xb9, 0x4e, 0x5e, 0x14, 0x98, 0x28, 0x78, 0xe6, 0x8c, 0xdb, 0x55, 0x9d, 0xcf,
+        0xe4, 0x49, 0xd8, 0xb9, 0x4e, 0x5e, 0x14, 0x82, 0xcf, 0xe4, 0x49, 0xd8, 0xb9, 0x4e, 0x5e,
+        0x14, 0xfc, 0xcf, 0xe4, 0x49, 0xd8, 0xb9, 0x4e, 0x5e, 0x14, 0x98, 0x28, 0x78, 0xe6, 0x8c,
+        0xdb, 0x55, 0x9d, 0x8f, 0x75, 0xeb, 0xe6, 0x1d, 0x33, 0x74, 0xfe, 0x8f, 0x75, 0xeb, 0xe6,
+        0x1d, 0x33, 0x74, 0xfe, 0x8f, 0x75, 0xeb, 0xe6, 0x1d, 0x33, 0x74, 0xfe, 0xb0, 0x98, 0x28,
+        0x78, 0xe6, 0x8c, 0xdb, 0x55, 0x9d, 0x98, 0x28, 0x78, 0xe6, 0x8c, 0xdb, 0x55, 0x9d, 0x4e,
+        0xa2, 0x21, 0x58, 0x04, 0xac, 0x67, 0x46, 0x5c, 0xcf, 0xe4, 0x49, 0xd8, 0xb9, 0x4e, 0x5e,
+        0x14,
+    ];
+
+    let mut frame_dec = FrameDecoder::new();
+    let mut source = &frame[..];
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert!(source.is_empty());
+    assert_eq!(frame_dec.collect().unwrap(), original);
+}
+
+#[test]
+fn test_error_offsets() {
+    use crate::decoding::block_decoder::DecodeBlockContentError;
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder, FrameDecoderError};
+
+    // a single compressed block with huffman coded literals and FSE compressed sequences. The block content starts
+    // at offset 10, the huffman table at 13 and the sequences section at 51
+    let frame = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x60, 0x6b, 0x00, 0x55, 0x03, 0x00, 0xa2, 0x82, 0x09, 0x10, 0xb0,
+        0xa9, 0x03, 0x07, 0x24, 0xad, 0xd1, 0x8d, 0xb0, 0x3d, 0xcb, 0x24, 0x49, 0x16, 0x0d, 0xc4,
+        0x40, 0x6d, 0x2f, 0xeb, 0xb7, 0xf5, 0x1b, 0xa8, 0x5c, 0x81, 0xaa, 0x5f, 0x29, 0x14, 0xe6,
+        0x94, 0x24, 0x74, 0xa0, 0xda, 0x02, 0x1b, 0xa8, 0x60, 0xb5, 0xae, 0x6c, 0x06, 0x10, 0x10,
+        0x81, 0x51, 0x8e, 0x3b, 0x10, 0x2a, 0x91, 0x90, 0x09, 0xac, 0xc2, 0x1c, 0x03, 0x94, 0x96,
+        0xf0, 0xea, 0xd5, 0xfb, 0xfa, 0xb0, 0xb9, 0x6f, 0x3c, 0xf4, 0x57, 0x24, 0x03, 0xcb, 0x94,
+        0x8d, 0x75, 0x55, 0xb6, 0xd2, 0xca, 0xa5, 0x18, 0xbb, 0x6d, 0x45, 0x21, 0x23, 0x48, 0x7d,
+        0xc2, 0x08, 0x89, 0x54, 0xf1, 0x29, 0xce, 0xc2, 0x4e, 0x90, 0x02,
+    ];
+
+    let decode = |frame: &[u8]| -> Result<(), FrameDecoderError> {
+        let mut frame_dec = FrameDecoder::new();
+        let mut source = frame;
+        frame_dec.reset(&mut source)?;
+        frame_dec.decode_blocks(&mut source, BlockDecodingStrategy::All)?;
+        Ok(())
+    };
+    decode(&frame).unwrap();
+
+    // the accuracy log of the huffman weights, the start of the literal lengths FSE table and a byte in a later FSE table
+    for corrupted in [14, 53, 64] {
+        let mut frame = frame;
+        frame[corrupted] = 0xff;
+        match decode(&frame) {
+            Err(FrameDecoderError::FailedToReadBlockBody(
+                DecodeBlockContentError::DecompressBlockError { at_offset, .. },
+            )) => {
+                let at_offset = at_offset as usize;
+                assert!(
+                    at_offset <= corrupted && corrupted - at_offset < 4,
+                    "corrupted byte {} but the error was reported at {}",
+                    corrupted,
+                    at_offset
+                );
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_raw_and_rle_literals_size_formats() {
+    use crate::blocks::block::BlockType;
+    use crate::blocks::literals_section::{LiteralsSection, LiteralsSectionType};
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+
+    let mut frame_dec = FrameDecoder::new();
+    for rle in [false, true] {
+        let type_bits = rle as u8;
+        for (num_literals, header_len) in [(31, 1), (32, 2), (4095, 2), (4096, 3), (100000, 3)] {
+            let literals: Vec<u8> = if rle {
+                vec![0xAB; num_literals as usize]
+            } else {
+                (0..num_literals).map(|x: u32| (x % 251) as u8).collect()
+            };
+            let header = match header_len {
+                1 => vec![(num_literals << 3) as u8 | type_bits],
+                2 => vec![
+                    (num_literals << 4) as u8 | 0b0100 | type_bits,
+                    (num_literals >> 4) as u8,
+                ],
+                _ => vec![
+                    (num_literals << 4) as u8 | 0b1100 | type_bits,
+                    (num_literals >> 4) as u8,
+                    (num_literals >> 12) as u8,
+                ],
+            };
+
+            let mut section = LiteralsSection::new();
+            assert_eq!(section.parse_from_header(&header).unwrap(), header_len);
+            if rle {
+                assert!(matches!(section.ls_type, LiteralsSectionType::RLE));
+            } else {
+                assert!(matches!(section.ls_type, LiteralsSectionType::Raw));
+            }
+            assert_eq!(section.regenerated_size, num_literals);
+
+            // compressed block with only these literals and no sequences, RLE literals store their byte once
+            let mut block = header;
+            if rle {
+                block.push(0xAB);
+            } else {
+                block.extend_from_slice(&literals);
+            }
+            block.push(0);
+            let frame = single_segment_frame(&[(BlockType::Compressed, &block)]);
+
+            let mut source = frame.as_slice();
+            frame_dec.reset(&mut source).unwrap();
+            frame_dec
+                .decode_blocks(&mut source, BlockDecodingStrategy::All)
+                .unwrap();
+            assert!(source.is_empty());
+            assert_eq!(frame_dec.collect().unwrap(), literals);
+        }
+    }
+}
+
+#[test]
+fn test_compressed_literals_largest_size_format() {
+    use crate::blocks::block::BlockType;
+    use crate::blocks::literals_section::{LiteralsSection, LiteralsSectionType};
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+
+    // a whole block of the literals 0 and 1, both with a one bit code
+    let num_literals = 128 * 1024;
+    let mut state = 7u32;
+    let literals: Vec<u8> = (0..num_literals)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) as u8 & 1
         })
-    ));
+        .collect();
+
+    // the decoder reads the streams backwards, starting with the bit below the highest set bit of the last byte
+    let encode_stream = |symbols: &[u8]| {
+        let mut bits: Vec<u8> = symbols.iter().rev().cloned().collect();
+        bits.push(1);
+        bits.chunks(8)
+            .map(|byte| {
+                byte.iter()
+                    .enumerate()
+                    .fold(0u8, |acc, (idx, &bit)| acc | (bit << idx))
+            })
+            .collect::<Vec<u8>>()
+    };
+    let streams: Vec<Vec<u8>> = literals
+        .chunks((num_literals as usize).div_ceil(4))
+        .map(encode_stream)
+        .collect();
+
+    // one directly represented weight of 1 for the literal 0, the weight of the literal 1 is implied
+    let mut section = vec![128, 0x10];
+    for stream in &streams[..3] {
+        section.extend_from_slice(&(stream.len() as u16).to_le_bytes());
+    }
+    for stream in &streams {
+        section.extend_from_slice(stream);
+    }
+
+    // both sizes need more than the 14 bit of the four byte header
+    let compressed_size = section.len() as u32;
+    assert!(compressed_size >= 1 << 14);
+    let header = vec![
+        0b1110 | (num_literals << 4) as u8,
+        (num_literals >> 4) as u8,
+        (num_literals >> 12) as u8 & 0x3f | (compressed_size << 6) as u8,
+        (compressed_size >> 2) as u8,
+        (compressed_size >> 10) as u8,
+    ];
+    let mut parsed = LiteralsSection::new();
+    assert_eq!(parsed.parse_from_header(&header).unwrap(), 5);
+    assert!(matches!(parsed.ls_type, LiteralsSectionType::Compressed));
+    assert_eq!(parsed.regenerated_size, num_literals);
+    assert_eq!(parsed.compressed_size, Some(compressed_size));
+    assert_eq!(parsed.num_streams, Some(4));
+
+    // compressed block with only these literals and no sequences
+    let mut block = header;
+    block.extend_from_slice(&section);
+    block.push(0);
+    let frame = single_segment_frame(&[(BlockType::Compressed, &block)]);
+
+    let mut frame_dec = FrameDecoder::new();
+    let mut source = frame.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert!(source.is_empty());
+    assert_eq!(frame_dec.collect().unwrap(), literals);
 }
 
-#[cfg(test)]
 #[test]
-fn test_frame_header_reading() {
-    use crate::frame;
+fn test_zero_sequences() {
+    use crate::blocks::literals_section::{LiteralsSection, LiteralsSectionType};
+    use crate::blocks::sequence_section::SequencesHeader;
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+
+    let mut state = 11u32;
+    let original: Vec<u8> = (0..300)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            b'a' + ((state >> 16) & 7) as u8
+        })
+        .collect();
+
+    // `zstd -19 --no-check` of the original. Without matches the only block holds huffman coded literals
+    // and a sequences section that is just the byte 0.
+    let frame = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x60, 0x2c, 0x00, 0x35, 0x04, 0x00, 0xc6, 0x92, 0x20, 0x07, 0xf0,
+        0x0d, 0x99, 0x99, 0x39, 0x9f, 0x7b, 0x1d, 0x00, 0x1d, 0x00, 0x1d, 0x00, 0x77, 0xec, 0x30,
+        0x38, 0xe4, 0xd0, 0x29, 0x51, 0xe0, 0x29, 0x77, 0x4d, 0x83, 0xd8, 0x33, 0x95, 0x3c, 0x40,
+        0xf2, 0x17, 0xf7, 0x81, 0xdb, 0xe1, 0x55, 0x7c, 0x83, 0x43, 0x03, 0x73, 0x40, 0xba, 0xe1,
+        0xe5, 0x32, 0x74, 0xaa, 0xaa, 0xf6, 0xa6, 0x67, 0x27, 0xb8, 0xef, 0xaa, 0x63, 0x50, 0xfd,
+        0x75, 0xef, 0x75, 0x12, 0x36, 0xd0, 0x09, 0x68, 0xba, 0x03, 0xb7, 0x7f, 0x43, 0x97, 0x1b,
+        0x92, 0xfb, 0x10, 0x39, 0x03, 0x7c, 0x2d, 0x7e, 0xe4, 0x7c, 0x85, 0x7b, 0x81, 0xcd, 0xe0,
+        0xe5, 0xc9, 0x88, 0x03, 0x35, 0xb4, 0x01, 0x6c, 0x03, 0x94, 0x47, 0x02, 0x74, 0xd7, 0x4c,
+        0x4b, 0xda, 0x5d, 0xd1, 0xeb, 0x9b, 0x5e, 0xa8, 0xc2, 0xd4, 0xb2, 0xdc, 0xe1, 0xcb, 0x2e,
+        0xae, 0xda, 0xbe, 0x4b, 0xb9, 0x28, 0x39, 0x03, 0x00,
+    ];
+
+    let mut literals = LiteralsSection::new();
+    literals.parse_from_header(&frame[10..]).unwrap();
+    assert!(matches!(literals.ls_type, LiteralsSectionType::Compressed));
+    assert_eq!(literals.regenerated_size, 300);
+    let mut sequences = SequencesHeader::new();
+    assert_eq!(
+        sequences
+            .parse_from_header(&frame[frame.len() - 1..])
+            .unwrap(),
+        1
+    );
+    assert_eq!(sequences.num_sequences, 0);
+
+    let mut frame_dec = FrameDecoder::new();
+    let mut source = &frame[..];
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert!(source.is_empty());
+    assert_eq!(frame_dec.collect().unwrap(), original);
+}
+
+#[test]
+fn test_mixed_sequence_modes() {
+    use crate::blocks::block::BlockType;
+    use crate::blocks::literals_section::{LiteralsSection, LiteralsSectionType};
+    use crate::blocks::sequence_section::{ModeType, SequencesHeader};
+    use crate::decoding::block_decoder;
+    use crate::decoding::scratch::DecoderScratch;
+    use crate::frame::read_frame_header;
     use std::fs;
 
-    let mut content = fs::File::open("./decodecorpus_files/z000088.zst").unwrap();
-    let (_frame, _) = frame::read_frame_header(&mut content).unwrap();
+    // blocks of the corpus that repeat the table of some symbol types while the others get new ones
+    for (file, mixed_block) in [("z000053", 2), ("z000071", 1), ("z000017", 2)] {
+        let content = fs::read(std::format!("./decodecorpus_files/{}.zst", file)).unwrap();
+        let original = fs::read(std::format!("./decodecorpus_files/{}", file)).unwrap();
+
+        let mut source = content.as_slice();
+        let (frame, _) = read_frame_header(&mut source).unwrap();
+        let mut scratch = DecoderScratch::new(frame.header.window_size().unwrap() as usize);
+        let mut block_dec = block_decoder::new();
+        for block_idx in 0.. {
+            let (header, _) = block_dec.read_block_header(&mut source).unwrap();
+            let before = [
+                scratch.fse.literal_lengths.describe(),
+                scratch.fse.offsets.describe(),
+                scratch.fse.match_lengths.describe(),
+            ];
+            block_dec
+                .decode_block_content(&header, &mut scratch, &mut source)
+                .unwrap();
+
+            if block_idx == mixed_block {
+                assert!(matches!(header.block_type, BlockType::Compressed));
+                let raw = scratch.block_content_buffer.as_slice();
+                let mut literals = LiteralsSection::new();
+                let header_len = literals.parse_from_header(raw).unwrap() as usize;
+                let literals_len = match literals.ls_type {
+                    LiteralsSectionType::Raw => literals.regenerated_size,
+                    LiteralsSectionType::RLE => 1,
+                    _ => literals.compressed_size.unwrap(),
+                };
+                let mut sequences = SequencesHeader::new();
+                sequences
+                    .parse_from_header(&raw[header_len + literals_len as usize..])
+                    .unwrap();
+                let modes = sequences.modes.unwrap();
+                let modes = [modes.ll_mode(), modes.of_mode(), modes.ml_mode()];
+                assert!(modes.iter().any(|mode| matches!(mode, ModeType::Repeat)));
+                assert!(modes.iter().any(|mode| !matches!(mode, ModeType::Repeat)));
+
+                let after = [
+                    &scratch.fse.literal_lengths,
+                    &scratch.fse.offsets,
+                    &scratch.fse.match_lengths,
+                ];
+                for (idx, mode) in modes.iter().enumerate() {
+                    match mode {
+                        ModeType::Repeat => assert_eq!(after[idx].describe(), before[idx]),
+                        ModeType::Predefined => assert_eq!(after[idx].accuracy_log, [6, 5, 6][idx]),
+                        _ => assert!(!after[idx].decode.is_empty()),
+                    }
+                }
+            }
+            if header.last_block {
+                break;
+            }
+        }
+        assert_eq!(scratch.buffer.drain(), original, "{}", file);
+    }
 }
 
 #[test]
@@ -371,6 +2558,164 @@ fn test_streaming() {
     }
 }
 
+#[test]
+#[cfg(feature = "std")]
+fn test_streaming_multiple_frames() {
+    use crate::streaming_decoder::StreamingDecoder;
+    use std::fs;
+    use std::io::Read;
+
+    let frame_1 = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    let frame_2 = fs::read("./decodecorpus_files/z000090.zst").unwrap();
+    let original_1 = fs::read("./decodecorpus_files/z000001").unwrap();
+    let original_2 = fs::read("./decodecorpus_files/z000090").unwrap();
+
+    // one frame followed by trailing data
+    let mut content = frame_1.clone();
+    content.extend_from_slice(b"trailing data");
+    let mut source = content.as_slice();
+    let mut stream = StreamingDecoder::new(&mut source).unwrap();
+    assert!(stream.has_next_frame().is_err());
+    assert!(matches!(
+        stream.next_frame(),
+        Err(crate::frame_decoder::FrameDecoderError::FrameNotFinished)
+    ));
+    let mut result = Vec::new();
+    stream.read_to_end(&mut result).unwrap();
+    assert_eq!(result, original_1);
+    assert!(!stream.has_next_frame().unwrap());
+    assert!(!stream.next_frame().unwrap());
+    assert_eq!(stream.peeked_bytes(), b"trai");
+    drop(stream);
+    assert_eq!(source, b"ling data");
+
+    // two frames with a skippable frame in between
+    let mut content = frame_1.clone();
+    content.extend_from_slice(&0x184D2A53u32.to_le_bytes());
+    content.extend_from_slice(&1000u32.to_le_bytes());
+    content.extend_from_slice(&[0xAB; 1000]);
+    content.extend_from_slice(&frame_2);
+    let mut stream = StreamingDecoder::new(content.as_slice()).unwrap();
+    let mut result = Vec::new();
+    stream.read_to_end(&mut result).unwrap();
+    assert_eq!(result, original_1);
+    assert!(stream.has_next_frame().unwrap());
+    // peeking twice does not consume anything
+    assert!(stream.has_next_frame().unwrap());
+    assert!(stream.next_frame().unwrap());
+    let mut result = Vec::new();
+    stream.read_to_end(&mut result).unwrap();
+    assert_eq!(result, original_2);
+    assert!(!stream.has_next_frame().unwrap());
+    assert!(stream.peeked_bytes().is_empty());
+
+    // the source ends inside of a skippable frame
+    let mut content = frame_1;
+    content.extend_from_slice(&0x184D2A50u32.to_le_bytes());
+    content.extend_from_slice(&1000u32.to_le_bytes());
+    let mut stream = StreamingDecoder::new(content.as_slice()).unwrap();
+    stream.read_to_end(&mut Vec::new()).unwrap();
+    assert!(stream.has_next_frame().unwrap());
+    assert!(matches!(
+        stream.next_frame(),
+        Err(crate::frame_decoder::FrameDecoderError::FailedToSkipFrame(
+            _
+        ))
+    ));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_decode_from_reader() {
+    use crate::decode_from_reader;
+    use std::fs;
+
+    struct TrickleReader<'a>(&'a [u8]);
+    impl std::io::Read for TrickleReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = usize::min(buf.len(), 1);
+            std::io::Read::read(&mut self.0, &mut buf[..len])
+        }
+    }
+
+    let frame_1 = fs::read("./decodecorpus_files/z000001.zst").unwrap();
+    let frame_2 = fs::read("./decodecorpus_files/z000090.zst").unwrap();
+    let mut original = fs::read("./decodecorpus_files/z000001").unwrap();
+
+    // without a size hint the frame content size is used, z000001 does not have one
+    let result = decode_from_reader(frame_1.as_slice(), None).unwrap();
+    assert_eq!(result, original);
+    let result = decode_from_reader(frame_1.as_slice(), Some(original.len())).unwrap();
+    assert_eq!(result, original);
+    assert_eq!(result.capacity(), original.len());
+    let result = decode_from_reader(TrickleReader(&frame_1), Some(10)).unwrap();
+    assert_eq!(result, original);
+
+    // all frames are decoded
+    let mut content = frame_1.clone();
+    content.extend_from_slice(&frame_2);
+    original.extend(fs::read("./decodecorpus_files/z000090").unwrap());
+    let result = decode_from_reader(TrickleReader(&content), None).unwrap();
+    assert_eq!(result, original);
+
+    // leading skippable frames are skipped too
+    let mut skippable = vec![0x50, 0x2a, 0x4d, 0x18, 0x03, 0x00, 0x00, 0x00, 1, 2, 3];
+    skippable.extend_from_slice(&[0x5f, 0x2a, 0x4d, 0x18, 0x00, 0x00, 0x00, 0x00]);
+    let mut with_skippable = skippable.clone();
+    with_skippable.extend_from_slice(&content);
+    let result = decode_from_reader(TrickleReader(&with_skippable), None).unwrap();
+    assert_eq!(result, original);
+    assert!(decode_from_reader(skippable.as_slice(), None).is_err());
+    assert!(decode_from_reader(&skippable[..10], None).is_err());
+
+    content.extend_from_slice(b"xx");
+    assert!(decode_from_reader(content.as_slice(), None).is_err());
+    assert!(decode_from_reader(&frame_1[..frame_1.len() - 1], None).is_err());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_streaming_read_vectored() {
+    use crate::streaming_decoder::StreamingDecoder;
+    use std::fs;
+    use std::io::{IoSliceMut, Read};
+
+    for file in ["z000022", "z000088"] {
+        let content = fs::read(std::format!("./decodecorpus_files/{}.zst", file)).unwrap();
+        let original = fs::read(std::format!("./decodecorpus_files/{}", file)).unwrap();
+
+        let mut sequential = Vec::new();
+        StreamingDecoder::new(content.as_slice())
+            .unwrap()
+            .read_to_end(&mut sequential)
+            .unwrap();
+        assert_eq!(sequential, original);
+
+        let mut stream = StreamingDecoder::new(content.as_slice()).unwrap();
+        let mut vectored = Vec::new();
+        let (mut a, mut b, mut c) = ([0u8; 100], [0u8; 7000], [0u8; 1]);
+        loop {
+            let mut bufs = [
+                IoSliceMut::new(&mut a),
+                IoSliceMut::new(&mut b),
+                IoSliceMut::new(&mut c),
+            ];
+            let read = stream.read_vectored(&mut bufs).unwrap();
+            if read == 0 {
+                break;
+            }
+            // the buffers are filled in order
+            let mut left = read;
+            for buf in [&a[..], &b[..], &c[..]] {
+                let n = usize::min(left, buf.len());
+                vectored.extend_from_slice(&buf[..n]);
+                left -= n;
+            }
+        }
+        assert_eq!(vectored, sequential);
+    }
+}
+
 #[test]
 #[cfg(not(feature = "std"))]
 fn test_streaming_no_std() {
@@ -453,6 +2798,269 @@ fn test_streaming_no_std() {
     }
 }
 
+#[test]
+fn test_current_frame_content_size() {
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use std::fs;
+
+    let mut frame_dec = FrameDecoder::new();
+    assert_eq!(frame_dec.current_frame_content_size(), None);
+
+    // header declares the content size
+    let mut content = fs::File::open("./decodecorpus_files/z000090.zst").unwrap();
+    frame_dec.reset(&mut content).unwrap();
+    let declared = frame_dec.current_frame_content_size();
+    frame_dec
+        .decode_blocks(&mut content, BlockDecodingStrategy::All)
+        .unwrap();
+    let result = frame_dec.collect().unwrap();
+    assert_eq!(declared, Some(result.len() as u64));
+
+    // header does not contain a content size
+    let mut content = fs::File::open("./decodecorpus_files/z000001.zst").unwrap();
+    frame_dec.reset(&mut content).unwrap();
+    assert_eq!(frame_dec.current_frame_content_size(), None);
+}
+
+#[test]
+fn test_salvage_truncated_frame() {
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use std::fs;
+
+    let content = fs::read("./decodecorpus_files/z000022.zst").unwrap();
+
+    let mut frame_dec = FrameDecoder::new();
+    let mut source = content.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    let expected = frame_dec.collect().unwrap();
+
+    // find out where the first two blocks end in the source
+    let mut source = content.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::UptoBlocks(2))
+        .unwrap();
+    assert!(!frame_dec.is_finished());
+    let consumed = frame_dec.bytes_read_from_source() as usize;
+
+    // cut the source in the middle of the third block
+    let truncated = &content[..consumed + 10];
+
+    let mut source = truncated;
+    frame_dec.reset(&mut source).unwrap();
+    assert!(frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .is_err());
+
+    frame_dec.set_salvage(true);
+    let mut source = truncated;
+    frame_dec.reset(&mut source).unwrap();
+    assert!(frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap());
+    assert!(frame_dec.is_finished());
+    assert!(frame_dec.is_truncated());
+    assert_eq!(frame_dec.blocks_decoded(), 2);
+    let salvaged = frame_dec.collect().unwrap();
+    assert!(!salvaged.is_empty());
+    assert!(salvaged.len() < expected.len());
+    assert_eq!(salvaged, expected[..salvaged.len()]);
+
+    // a complete frame is not affected by salvage mode
+    let mut source = content.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert!(!frame_dec.is_truncated());
+    assert_eq!(frame_dec.collect().unwrap(), expected);
+}
+
+#[test]
+fn test_frame_decoder_options() {
+    use crate::frame_decoder::{
+        BlockDecodingStrategy, FrameDecoder, FrameDecoderError, FrameDecoderOptions,
+    };
+    use std::fs;
+
+    // z000022.zst needs a 2.5MB window
+    let content = fs::read("./decodecorpus_files/z000022.zst").unwrap();
+    let options = FrameDecoderOptions::new()
+        .max_window_size(1024 * 1024)
+        .salvage(true);
+
+    // checked on the first use of the decoder as well as on every reset
+    let mut frame_dec = FrameDecoder::with_options(options.clone());
+    for _ in 0..2 {
+        let err = frame_dec.reset(content.as_slice());
+        assert!(matches!(
+            err,
+            Err(FrameDecoderError::WindowSizeTooBig {
+                requested: 2621440,
+                max: 1048576
+            })
+        ));
+    }
+
+    let mut frame_dec = FrameDecoder::with_options(options.max_window_size(4 * 1024 * 1024));
+    let mut source = &content[..content.len() - 100];
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert!(frame_dec.is_truncated());
+
+    // defaults
+    let mut frame_dec = FrameDecoder::with_options(FrameDecoderOptions::default());
+    let mut source = &content[..content.len() - 100];
+    frame_dec.reset(&mut source).unwrap();
+    assert!(frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .is_err());
+}
+
+#[test]
+fn test_decode_with() {
+    use crate::frame_decoder::FrameDecoder;
+    use std::fs;
+
+    let mut frame_dec = FrameDecoder::new();
+    for file in ["z000022", "z000088", "z000090"] {
+        let content = fs::read(std::format!("./decodecorpus_files/{}.zst", file)).unwrap();
+        let original = fs::read(std::format!("./decodecorpus_files/{}", file)).unwrap();
+
+        let mut result = Vec::new();
+        let mut chunks = 0;
+        frame_dec
+            .decode_with(content.as_slice(), |chunk| {
+                result.extend_from_slice(chunk);
+                chunks += 1;
+            })
+            .unwrap();
+        assert!(frame_dec.is_finished());
+        assert_eq!(frame_dec.can_collect(), 0);
+        assert_eq!(result, original);
+        assert!(chunks >= 1);
+    }
+}
+
+#[test]
+fn test_decode_and_hash() {
+    use crate::frame_decoder::FrameDecoder;
+    use core::hash::Hasher;
+    use std::fs;
+
+    // FNV-1a, the result does not depend on how the bytes are split up between calls to write
+    struct Fnv(u64);
+    impl Hasher for Fnv {
+        fn write(&mut self, bytes: &[u8]) {
+            for b in bytes {
+                self.0 = (self.0 ^ u64::from(*b)).wrapping_mul(0x100000001b3);
+            }
+        }
+        fn finish(&self) -> u64 {
+            self.0
+        }
+    }
+
+    let mut frame_dec = FrameDecoder::new();
+    for file in ["z000022", "z000088"] {
+        let content = fs::read(std::format!("./decodecorpus_files/{}.zst", file)).unwrap();
+        let original = fs::read(std::format!("./decodecorpus_files/{}", file)).unwrap();
+
+        let mut expected = Fnv(0xcbf29ce484222325);
+        expected.write(&original);
+
+        let mut hasher = Fnv(0xcbf29ce484222325);
+        let digest = frame_dec
+            .decode_and_hash(content.as_slice(), &mut hasher)
+            .unwrap();
+        assert_eq!(digest, expected.finish());
+        assert_eq!(hasher.finish(), expected.finish());
+    }
+}
+
+#[test]
+fn test_collect_slice() {
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use std::fs;
+
+    let content = fs::read("./decodecorpus_files/z000022.zst").unwrap();
+    let original = fs::read("./decodecorpus_files/z000022").unwrap();
+
+    let mut frame_dec = FrameDecoder::new();
+    assert!(frame_dec.collect_slice(10).is_empty());
+
+    let mut source = content.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    let mut result = Vec::new();
+    loop {
+        frame_dec
+            .decode_blocks(&mut source, BlockDecodingStrategy::UptoBlocks(1))
+            .unwrap();
+        loop {
+            let slice = frame_dec.collect_slice(10_000);
+            assert!(slice.len() <= 10_000);
+            if slice.is_empty() {
+                break;
+            }
+            let len = slice.len();
+            result.extend_from_slice(slice);
+            frame_dec.consume(len);
+        }
+        if frame_dec.is_finished() {
+            break;
+        }
+    }
+    assert_eq!(frame_dec.can_collect(), 0);
+    assert_eq!(result, original);
+    #[cfg(feature = "hash")]
+    assert_eq!(
+        frame_dec.get_calculated_checksum(),
+        frame_dec.get_checksum_from_data()
+    );
+}
+
+#[test]
+fn test_shrink_to_fit() {
+    use crate::frame_decoder::{BlockDecodingStrategy, FrameDecoder};
+    use std::fs;
+
+    let mut frame_dec = FrameDecoder::new();
+    frame_dec.shrink_to_fit();
+
+    // uncollected bytes survive shrinking
+    let content = fs::read("./decodecorpus_files/z000022.zst").unwrap();
+    let mut source = content.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    frame_dec.shrink_to_fit();
+    assert_eq!(
+        frame_dec.collect().unwrap(),
+        fs::read("./decodecorpus_files/z000022").unwrap()
+    );
+
+    // the decoder is still usable for the next frame
+    frame_dec.shrink_to_fit();
+    let content = fs::read("./decodecorpus_files/z000088.zst").unwrap();
+    let mut source = content.as_slice();
+    frame_dec.reset(&mut source).unwrap();
+    frame_dec
+        .decode_blocks(&mut source, BlockDecodingStrategy::All)
+        .unwrap();
+    assert_eq!(
+        frame_dec.collect().unwrap(),
+        fs::read("./decodecorpus_files/z000088").unwrap()
+    );
+}
+
+#[cfg(all(test, feature = "std"))]
+pub mod alloc_test;
 pub mod bit_reader;
 pub mod decode_corpus;
 pub mod dict_test;